@@ -114,6 +114,14 @@ mod gen {
         0xFFFFFF
     }
 
+    fn default_max_rules_per_sheet() -> i64 {
+        100_000
+    }
+
+    fn enabled_by_default() -> bool {
+        true
+    }
+
     build_structs! {
         // type of the accessors
         accessor_type = crate::pref_util::Accessor::<Prefs, crate::pref_util::PrefValue>,
@@ -136,6 +144,12 @@ mod gen {
                         enabled: bool,
                     },
                 },
+                cssom: {
+                    /// The number of rules a single stylesheet can hold before
+                    /// `insertRule()` throws a `QuotaExceededError`.
+                    #[serde(default = "default_max_rules_per_sheet")]
+                    max_rules_per_sheet: i64,
+                },
             },
             devtools: {
                 server: {
@@ -172,6 +186,15 @@ mod gen {
                     dblclick_timeout: i64,
                     dblclick_dist: i64,
                 },
+                dommatrix: {
+                    strict_origins: {
+                        /// Make the DOMMatrix scale methods that take an origin
+                        /// throw for a NaN origin instead of producing a NaN
+                        /// matrix.
+                        #[serde(default)]
+                        enabled: bool,
+                    },
+                },
                 forcetouch: {
                     enabled: bool,
                 },
@@ -207,6 +230,20 @@ mod gen {
                         allowed_in_nonsecure_contexts: bool,
                     }
                 },
+                performance: {
+                    layout_shift: {
+                        /// Expose the Layout Instability API and report
+                        /// `layout-shift` entries.
+                        #[serde(default = "enabled_by_default")]
+                        enabled: bool,
+                    },
+                    timing_jitter: {
+                        /// Jitter `performance.now()` within its 10µs bucket
+                        /// instead of always rounding down.
+                        #[serde(default)]
+                        enabled: bool,
+                    },
+                },
                 script: {
                     asynch: bool,
                 },
@@ -257,6 +294,10 @@ mod gen {
                     },
                 },
                 testing: {
+                    /// Treat every global as cross-origin isolated, since COOP
+                    /// and COEP are not implemented yet.
+                    #[serde(default)]
+                    cross_origin_isolated: bool,
                     element: {
                         activation: {
                             #[serde(default)]
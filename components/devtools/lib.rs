@@ -618,6 +618,16 @@ fn run_server(
                 pipeline,
                 title,
             ),
+            DevtoolsControlMsg::FromScript(ScriptToDevtoolsControlMsg::StyleSheetChanged(
+                pipeline,
+                change,
+            )) => {
+                // Only script reports these so far. The stylesheets actor
+                // answers getStyleSheets with an empty list and has no actor
+                // per sheet that a change could be reported through, so
+                // there is nothing to forward this to yet.
+                debug!("Stylesheet changed in {:?}: {:?}", pipeline, change);
+            },
             DevtoolsControlMsg::FromScript(ScriptToDevtoolsControlMsg::NewGlobal(
                 ids,
                 script_sender,
@@ -352,17 +352,21 @@ impl PaintTimeMetrics {
 
         if let Some(pending_metric) = self.pending_metrics.borrow_mut().remove(&epoch) {
             let profiler_metadata = pending_metric.0;
-            set_metric(
-                self,
-                profiler_metadata.clone(),
-                ProgressiveWebMetricType::FirstPaint,
-                ProfilerCategory::TimeToFirstPaint,
-                &self.first_paint,
-                Some(paint_time),
-                &self.url,
-            );
-
-            if pending_metric.1 {
+            // Each paint milestone is only reported once, even if an earlier
+            // paint already set one of them.
+            if self.first_paint.get().is_none() {
+                set_metric(
+                    self,
+                    profiler_metadata.clone(),
+                    ProgressiveWebMetricType::FirstPaint,
+                    ProfilerCategory::TimeToFirstPaint,
+                    &self.first_paint,
+                    Some(paint_time),
+                    &self.url,
+                );
+            }
+
+            if pending_metric.1 && self.first_contentful_paint.get().is_none() {
                 set_metric(
                     self,
                     profiler_metadata,
@@ -727,6 +727,8 @@ pub async fn http_fetch(
     if request.service_workers_mode == ServiceWorkersMode::All {
         // TODO: Substep 1
         // Set response to the result of invoking handle fetch for request.
+        // Handle fetch should record `ResourceAttribute::WorkerStart` when a
+        // service worker starts handling the request.
 
         // Substep 2
         if let Some(ref res) = response {
@@ -1782,6 +1784,11 @@ async fn http_network_fetch(
         context.timing.lock().unwrap().mark_timing_check_failed();
     }
 
+    context.timing.lock().unwrap().set_response_status(
+        res.status().as_u16(),
+        request.response_tainting == ResponseTainting::Opaque,
+    );
+
     let timing = context.timing.lock().unwrap().clone();
     let mut response = Response::new(url.clone(), timing);
 
@@ -1841,6 +1848,16 @@ async fn http_network_fetch(
         }
     }
 
+    // The decoder only hands us decoded chunks, so the encoded body size
+    // is only known from the headers when a content coding was applied.
+    let encoded_body_size = if response.headers.contains_key(header::CONTENT_ENCODING) {
+        response
+            .headers
+            .typed_get::<ContentLength>()
+            .map(|ContentLength(length)| length)
+    } else {
+        None
+    };
     let done_sender2 = done_sender.clone();
     let done_sender3 = done_sender.clone();
     let timing_ptr2 = context.timing.clone();
@@ -1874,11 +1891,14 @@ async fn http_network_fetch(
                     ResponseBody::Receiving(ref mut body) => mem::replace(body, vec![]),
                     _ => vec![],
                 };
+                let decoded_body_size = completed_body.len() as u64;
                 *body = ResponseBody::Done(completed_body);
-                timing_ptr2
-                    .lock()
-                    .unwrap()
-                    .set_attribute(ResourceAttribute::ResponseEnd);
+                let mut timing = timing_ptr2.lock().unwrap();
+                timing.set_body_sizes(
+                    encoded_body_size.unwrap_or(decoded_body_size),
+                    decoded_body_size,
+                );
+                timing.set_attribute(ResourceAttribute::ResponseEnd);
                 let _ = done_sender2.send(Data::Done);
                 future::ready(Ok(()))
             })
@@ -125,6 +125,32 @@ fn test_fetch_response_body_matches_const_message() {
     };
 }
 
+#[test]
+fn test_fetch_response_records_body_sizes() {
+    static MESSAGE: &'static [u8] = b"Hello World!";
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        *response.body_mut() = MESSAGE.to_vec().into();
+    };
+    let (server, url) = make_server(handler);
+
+    let origin = Origin::Origin(url.origin());
+    let mut request = Request::new(
+        url,
+        Some(origin),
+        Referrer::NoReferrer,
+        None,
+        HttpsState::None,
+    );
+    let fetch_response = fetch(&mut request, None);
+    let _ = server.close();
+
+    let timing = fetch_response.get_resource_timing();
+    let timing = timing.lock().unwrap();
+    assert_eq!(timing.decoded_body_size, MESSAGE.len() as u64);
+    assert_eq!(timing.encoded_body_size, MESSAGE.len() as u64);
+    assert_eq!(timing.transfer_size, MESSAGE.len() as u64 + 300);
+}
+
 #[test]
 fn test_fetch_aboutblank() {
     let url = ServoUrl::parse("about:blank").unwrap();
@@ -1087,6 +1113,19 @@ fn test_fetch_redirect_count_ceiling() {
     };
 }
 
+#[test]
+fn test_fetch_redirect_records_redirect_timings() {
+    static MESSAGE: &'static [u8] = b"redirected";
+    let fetch_response = setup_server_and_fetch(MESSAGE, 2);
+    assert!(!fetch_response.is_network_error());
+
+    let timing = fetch_response.get_resource_timing();
+    let timing = timing.lock().unwrap();
+    assert_eq!(timing.redirect_count, 2);
+    assert!(timing.redirect_start > 0);
+    assert!(timing.redirect_end >= timing.redirect_start);
+}
+
 #[test]
 fn test_fetch_redirect_count_failure() {
     static MESSAGE: &'static [u8] = b"this message shouldn't be reachable";
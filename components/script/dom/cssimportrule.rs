@@ -7,8 +7,9 @@ use servo_arc::Arc;
 use style::shared_lock::{Locked, ToCssWithGuard};
 use style::stylesheets::ImportRule;
 
-use crate::dom::bindings::reflector::reflect_dom_object;
-use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::codegen::Bindings::CSSImportRuleBinding::CSSImportRuleMethods;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::cssrule::{CSSRule, SpecificCSSRule};
 use crate::dom::cssstylesheet::CSSStyleSheet;
@@ -20,6 +21,7 @@ pub struct CSSImportRule {
     #[ignore_malloc_size_of = "Arc"]
     #[no_trace]
     import_rule: Arc<Locked<ImportRule>>,
+    stylesheet: MutNullableDom<CSSStyleSheet>,
 }
 
 impl CSSImportRule {
@@ -30,6 +32,7 @@ impl CSSImportRule {
         CSSImportRule {
             cssrule: CSSRule::new_inherited(parent_stylesheet),
             import_rule: import_rule,
+            stylesheet: MutNullableDom::new(None),
         }
     }
 
@@ -60,3 +63,37 @@ impl SpecificCSSRule for CSSImportRule {
             .into()
     }
 }
+
+impl CSSImportRuleMethods for CSSImportRule {
+    // https://drafts.csswg.org/cssom/#dom-cssimportrule-stylesheet
+    fn GetStyleSheet(&self) -> Option<DomRoot<CSSStyleSheet>> {
+        if let Some(stylesheet) = self.stylesheet.get() {
+            return Some(stylesheet);
+        }
+        // An @import with a false supports() condition is never fetched, and
+        // has no stylesheet.
+        let (imported, location) = {
+            let guard = self.cssrule.shared_lock().read();
+            let import_rule = self.import_rule.read_with(&guard);
+            let imported = import_rule.stylesheet.as_sheet()?.clone();
+            // The sheet may still be loading, so its location is the URL the
+            // rule requested rather than where a redirect might end up.
+            let location = import_rule
+                .url
+                .url()
+                .map(|url| DOMString::from(url.as_str()));
+            (imported, location)
+        };
+        let stylesheet = CSSStyleSheet::new_imported(
+            self.global().as_window(),
+            &self.cssrule,
+            "text/css".into(),
+            location,
+            // Imported style sheets have no title.
+            None,
+            imported,
+        );
+        self.stylesheet.set(Some(&stylesheet));
+        Some(stylesheet)
+    }
+}
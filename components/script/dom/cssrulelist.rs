@@ -6,9 +6,10 @@
 
 use dom_struct::dom_struct;
 use servo_arc::Arc;
-use style::shared_lock::Locked;
+use servo_config::pref;
+use style::shared_lock::{Locked, SharedRwLockReadGuard};
 use style::stylesheets::{
-    AllowImportRules, CssRules, CssRulesHelpers, KeyframesRule, RulesMutateError,
+    AllowImportRules, CssRule, CssRules, CssRulesHelpers, KeyframesRule, RulesMutateError,
     StylesheetLoader as StyleStylesheetLoader,
 };
 
@@ -99,6 +100,15 @@ impl CSSRuleList {
             panic!("Called insert_rule on non-CssRule-backed CSSRuleList");
         };
 
+        {
+            // The limit is on the whole sheet, not just on the list this rule
+            // goes into, or nesting rules in @media would get around it.
+            let guard = self.parent_stylesheet.shared_lock().read();
+            let sheet_rules = &self.parent_stylesheet.style_stylesheet().contents.rules;
+            let rule_count = count_rules(sheet_rules.read_with(&guard), &guard);
+            check_rule_limit(rule_count, pref!(css.cssom.max_rules_per_sheet))?;
+        }
+
         let global = self.global();
         let window = global.as_window();
         let index = idx as usize;
@@ -106,7 +116,7 @@ impl CSSRuleList {
         let parent_stylesheet = self.parent_stylesheet.style_stylesheet();
         let owner = self
             .parent_stylesheet
-            .get_owner()
+            .owner_element()
             .map(DomRoot::downcast::<HTMLElement>)
             .flatten();
         let loader = owner
@@ -195,6 +205,38 @@ impl CSSRuleList {
     }
 }
 
+/// The number of rules in `rules`, including those nested in grouping rules
+/// like `@media` and in style rules. The rules of imported stylesheets are not
+/// included: they count against their own sheet.
+pub fn count_rules(rules: &CssRules, guard: &SharedRwLockReadGuard) -> usize {
+    rules
+        .0
+        .iter()
+        .map(|rule| {
+            let nested = match *rule {
+                CssRule::Style(ref rule) => rule.read_with(guard).rules.as_ref(),
+                CssRule::Media(ref rule) => Some(&rule.rules),
+                CssRule::Supports(ref rule) => Some(&rule.rules),
+                CssRule::Document(ref rule) => Some(&rule.rules),
+                CssRule::Container(ref rule) => Some(&rule.rules),
+                CssRule::LayerBlock(ref rule) => Some(&rule.rules),
+                _ => None,
+            };
+            1 + nested.map_or(0, |rules| count_rules(rules.read_with(guard), guard))
+        })
+        .sum()
+}
+
+/// Check that a stylesheet holding `rule_count` rules can take one more, given
+/// the `css.cssom.max_rules_per_sheet` pref. The limit is far above what real
+/// stylesheets use, and only stops scripts from growing a sheet without bound.
+pub fn check_rule_limit(rule_count: usize, max_rules: i64) -> ErrorResult {
+    if rule_count as i64 >= max_rules {
+        return Err(Error::QuotaExceeded);
+    }
+    Ok(())
+}
+
 impl CSSRuleListMethods for CSSRuleList {
     // https://drafts.csswg.org/cssom/#ref-for-dom-cssrulelist-item-1
     fn Item(&self, idx: u32) -> Option<DomRoot<CSSRule>> {
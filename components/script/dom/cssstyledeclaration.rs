@@ -117,7 +117,7 @@ impl CSSStyleOwner {
                 if changed {
                     // If this is changed, see also
                     // CSSStyleRule::SetSelectorText, which does the same thing.
-                    if let Some(owner) = rule.parent_stylesheet().get_owner() {
+                    if let Some(owner) = rule.parent_stylesheet().owner_element() {
                         stylesheets_owner_from_node(owner.upcast::<Node>())
                             .invalidate_stylesheets();
                     }
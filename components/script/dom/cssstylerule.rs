@@ -116,7 +116,7 @@ impl CSSStyleRuleMethods for CSSStyleRule {
             let mut guard = self.cssrule.shared_lock().write();
             let stylerule = self.stylerule.write_with(&mut guard);
             mem::swap(&mut stylerule.selectors, &mut s);
-            if let Some(owner) = self.cssrule.parent_stylesheet().get_owner() {
+            if let Some(owner) = self.cssrule.parent_stylesheet().owner_element() {
                 stylesheets_owner_from_node(owner.upcast::<Node>()).invalidate_stylesheets();
             }
         }
@@ -4,17 +4,23 @@
 
 use std::cell::Cell;
 
+use devtools_traits::{ScriptToDevtoolsControlMsg, StyleSheetChange, StyleSheetChangeKind};
 use dom_struct::dom_struct;
+use ipc_channel::ipc::IpcSender;
+use msg::constellation_msg::PipelineId;
 use servo_arc::Arc;
 use style::shared_lock::SharedRwLock;
 use style::stylesheets::Stylesheet as StyleStyleSheet;
 
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::CSSStyleSheetBinding::CSSStyleSheetMethods;
+use crate::dom::bindings::codegen::Bindings::StyleSheetBinding::StyleSheetMethods;
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
-use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
+use crate::dom::cssrule::CSSRule;
 use crate::dom::cssrulelist::{CSSRuleList, RulesSource};
 use crate::dom::element::Element;
 use crate::dom::medialist::MediaList;
@@ -22,10 +28,78 @@ use crate::dom::node::{stylesheets_owner_from_node, Node};
 use crate::dom::stylesheet::StyleSheet;
 use crate::dom::window::Window;
 
+/// What a stylesheet belongs to: the element it was created for, or the
+/// `@import` rule it was imported by. A stylesheet never has both an owner
+/// node and an owner rule, and a constructed stylesheet has neither.
+/// <https://drafts.csswg.org/cssom/#concept-css-style-sheet-owner-node>
+#[derive(Clone, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum StyleSheetOwner<N, R> {
+    Neither,
+    Node(N),
+    Rule(R),
+}
+
+impl<N, R> StyleSheetOwner<N, R> {
+    pub fn node(&self) -> Option<&N> {
+        match *self {
+            StyleSheetOwner::Node(ref node) => Some(node),
+            _ => None,
+        }
+    }
+
+    pub fn rule(&self) -> Option<&R> {
+        match *self {
+            StyleSheetOwner::Rule(ref rule) => Some(rule),
+            _ => None,
+        }
+    }
+
+    /// Set or clear the owner node. An imported stylesheet can't be given
+    /// one, which is asserted against in debug builds.
+    pub fn set_node(&mut self, node: Option<N>) {
+        match node {
+            Some(node) => {
+                debug_assert!(
+                    self.rule().is_none(),
+                    "A stylesheet with an owner rule cannot have an owner node"
+                );
+                *self = StyleSheetOwner::Node(node);
+            },
+            None if self.node().is_some() => *self = StyleSheetOwner::Neither,
+            None => {},
+        }
+    }
+
+    /// Set or clear the owner rule. Imported stylesheets have no owner node,
+    /// so setting an owner rule clears it.
+    pub fn set_rule(&mut self, rule: Option<R>) {
+        match rule {
+            Some(rule) => *self = StyleSheetOwner::Rule(rule),
+            None if self.rule().is_some() => *self = StyleSheetOwner::Neither,
+            None => {},
+        }
+    }
+}
+
+/// Send devtools, if it is listening on `chan`, the change to the rules of a
+/// stylesheet of `pipeline_id` that `change` describes.
+pub fn send_style_sheet_change(
+    chan: Option<&IpcSender<ScriptToDevtoolsControlMsg>>,
+    pipeline_id: PipelineId,
+    change: impl FnOnce() -> StyleSheetChange,
+) {
+    if let Some(chan) = chan {
+        let _ = chan.send(ScriptToDevtoolsControlMsg::StyleSheetChanged(
+            pipeline_id,
+            change(),
+        ));
+    }
+}
+
 #[dom_struct]
 pub struct CSSStyleSheet {
     stylesheet: StyleSheet,
-    owner: MutNullableDom<Element>,
+    owner: DomRefCell<StyleSheetOwner<Dom<Element>, Dom<CSSRule>>>,
     rulelist: MutNullableDom<CSSRuleList>,
     #[ignore_malloc_size_of = "Arc"]
     #[no_trace]
@@ -35,7 +109,7 @@ pub struct CSSStyleSheet {
 
 impl CSSStyleSheet {
     fn new_inherited(
-        owner: &Element,
+        owner: StyleSheetOwner<&Element, &CSSRule>,
         type_: DOMString,
         href: Option<DOMString>,
         title: Option<DOMString>,
@@ -43,7 +117,11 @@ impl CSSStyleSheet {
     ) -> CSSStyleSheet {
         CSSStyleSheet {
             stylesheet: StyleSheet::new_inherited(type_, href, title),
-            owner: MutNullableDom::new(Some(owner)),
+            owner: DomRefCell::new(match owner {
+                StyleSheetOwner::Neither => StyleSheetOwner::Neither,
+                StyleSheetOwner::Node(node) => StyleSheetOwner::Node(Dom::from_ref(node)),
+                StyleSheetOwner::Rule(rule) => StyleSheetOwner::Rule(Dom::from_ref(rule)),
+            }),
             rulelist: MutNullableDom::new(None),
             style_stylesheet: stylesheet,
             origin_clean: Cell::new(true),
@@ -61,7 +139,33 @@ impl CSSStyleSheet {
     ) -> DomRoot<CSSStyleSheet> {
         reflect_dom_object(
             Box::new(CSSStyleSheet::new_inherited(
-                owner, type_, href, title, stylesheet,
+                StyleSheetOwner::Node(owner),
+                type_,
+                href,
+                title,
+                stylesheet,
+            )),
+            window,
+        )
+    }
+
+    /// Create the stylesheet imported by `owner_rule`, an `@import` rule.
+    #[allow(crown::unrooted_must_root)]
+    pub fn new_imported(
+        window: &Window,
+        owner_rule: &CSSRule,
+        type_: DOMString,
+        href: Option<DOMString>,
+        title: Option<DOMString>,
+        stylesheet: Arc<StyleStyleSheet>,
+    ) -> DomRoot<CSSStyleSheet> {
+        reflect_dom_object(
+            Box::new(CSSStyleSheet::new_inherited(
+                StyleSheetOwner::Rule(owner_rule),
+                type_,
+                href,
+                title,
+                stylesheet,
             )),
             window,
         )
@@ -79,18 +183,61 @@ impl CSSStyleSheet {
     }
 
     pub fn get_owner(&self) -> Option<DomRoot<Element>> {
-        self.owner.get()
+        self.owner
+            .borrow()
+            .node()
+            .map(|node| DomRoot::from_ref(&**node))
+    }
+
+    /// The element this stylesheet takes part in the stylesheets of: its
+    /// owner node or, for an imported stylesheet, that of the stylesheet
+    /// importing it.
+    pub fn owner_element(&self) -> Option<DomRoot<Element>> {
+        match self.get_owner_rule() {
+            Some(rule) => rule.parent_stylesheet().owner_element(),
+            None => self.get_owner(),
+        }
     }
 
     pub fn set_disabled(&self, disabled: bool) {
-        if self.style_stylesheet.set_disabled(disabled) && self.get_owner().is_some() {
-            stylesheets_owner_from_node(self.get_owner().unwrap().upcast::<Node>())
-                .invalidate_stylesheets();
+        if self.style_stylesheet.set_disabled(disabled) {
+            self.invalidate_owner_stylesheets();
         }
     }
 
+    /// Mark the stylesheets of the document or shadow root owning this
+    /// stylesheet as dirty, so that changes made through the CSSOM are
+    /// picked up on the next restyle.
+    pub fn invalidate_owner_stylesheets(&self) {
+        if let Some(owner) = self.owner_element() {
+            stylesheets_owner_from_node(owner.upcast::<Node>()).invalidate_stylesheets();
+        }
+    }
+
+    /// Let devtools know that the rules of this stylesheet were changed
+    /// through the CSSOM, so the inspector can refresh its view.
+    fn notify_devtools(&self, kind: StyleSheetChangeKind) {
+        let global = self.global();
+        send_style_sheet_change(global.devtools_chan(), global.pipeline_id(), || {
+            StyleSheetChange {
+                owner_node: self
+                    .get_owner()
+                    .map(|owner| owner.upcast::<Node>().unique_id()),
+                href: self.stylesheet.GetHref().map(String::from),
+                kind,
+            }
+        });
+    }
+
     pub fn set_owner(&self, value: Option<&Element>) {
-        self.owner.set(value);
+        self.owner.borrow_mut().set_node(value.map(Dom::from_ref));
+    }
+
+    pub fn get_owner_rule(&self) -> Option<DomRoot<CSSRule>> {
+        self.owner
+            .borrow()
+            .rule()
+            .map(|rule| DomRoot::from_ref(&**rule))
     }
 
     pub fn shared_lock(&self) -> &SharedRwLock {
@@ -105,6 +252,15 @@ impl CSSStyleSheet {
         self.origin_clean.set(origin_clean);
     }
 
+    /// Whether this stylesheet is origin-clean. Imported stylesheets are
+    /// loaded on behalf of the element of the stylesheet importing them, which
+    /// gets their origin-clean flag, so they go by the flag of that stylesheet.
+    fn is_origin_clean(&self) -> bool {
+        self.origin_clean.get() &&
+            self.get_owner_rule()
+                .map_or(true, |rule| rule.parent_stylesheet().is_origin_clean())
+    }
+
     pub fn medialist(&self) -> DomRoot<MediaList> {
         MediaList::new(
             self.global().as_window(),
@@ -115,9 +271,14 @@ impl CSSStyleSheet {
 }
 
 impl CSSStyleSheetMethods for CSSStyleSheet {
+    // https://drafts.csswg.org/cssom/#dom-cssstylesheet-ownerrule
+    fn GetOwnerRule(&self) -> Option<DomRoot<CSSRule>> {
+        self.get_owner_rule()
+    }
+
     // https://drafts.csswg.org/cssom/#dom-cssstylesheet-cssrules
     fn GetCssRules(&self) -> Fallible<DomRoot<CSSRuleList>> {
-        if !self.origin_clean.get() {
+        if !self.is_origin_clean() {
             return Err(Error::Security);
         }
         Ok(self.rulelist())
@@ -125,18 +286,23 @@ impl CSSStyleSheetMethods for CSSStyleSheet {
 
     // https://drafts.csswg.org/cssom/#dom-cssstylesheet-insertrule
     fn InsertRule(&self, rule: DOMString, index: u32) -> Fallible<u32> {
-        if !self.origin_clean.get() {
+        if !self.is_origin_clean() {
             return Err(Error::Security);
         }
-        self.rulelist()
-            .insert_rule(&rule, index, /* nested */ false)
+        let index = self
+            .rulelist()
+            .insert_rule(&rule, index, /* nested */ false)?;
+        self.notify_devtools(StyleSheetChangeKind::RuleInserted(index));
+        Ok(index)
     }
 
     // https://drafts.csswg.org/cssom/#dom-cssstylesheet-deleterule
     fn DeleteRule(&self, index: u32) -> ErrorResult {
-        if !self.origin_clean.get() {
+        if !self.is_origin_clean() {
             return Err(Error::Security);
         }
-        self.rulelist().remove_rule(index)
+        self.rulelist().remove_rule(index)?;
+        self.notify_devtools(StyleSheetChangeKind::RuleDeleted(index));
+        Ok(())
     }
 }
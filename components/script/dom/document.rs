@@ -154,6 +154,7 @@ use crate::dom::node::{
 use crate::dom::nodeiterator::NodeIterator;
 use crate::dom::nodelist::NodeList;
 use crate::dom::pagetransitionevent::PageTransitionEvent;
+use crate::dom::performancenavigationtiming::previous_document_unload_timings;
 use crate::dom::processinginstruction::ProcessingInstruction;
 use crate::dom::promise::Promise;
 use crate::dom::range::Range;
@@ -340,6 +341,10 @@ pub struct Document {
     load_event_end: Cell<u64>,
     unload_event_start: Cell<u64>,
     unload_event_end: Cell<u64>,
+    /// When this document's own unload event was dispatched, which is reported
+    /// by the navigation timing of the document replacing it.
+    fired_unload_event_start: Cell<u64>,
+    fired_unload_event_end: Cell<u64>,
     /// <https://html.spec.whatwg.org/multipage/#concept-document-https-state>
     #[no_trace]
     https_state: Cell<HttpsState>,
@@ -2219,7 +2224,9 @@ impl Document {
             event.set_trusted(true);
             let event_target = self.window.upcast::<EventTarget>();
             let has_listeners = event_target.has_listeners_for(&atom!("unload"));
+            update_with_current_time_ms(&self.fired_unload_event_start);
             let _ = self.window.dispatch_event_with_target_override(&event);
+            update_with_current_time_ms(&self.fired_unload_event_end);
             self.fired_unload.set(true);
             // Step 9
             if has_listeners {
@@ -2707,6 +2714,19 @@ impl Document {
         self.unload_event_end.get()
     }
 
+    /// Report the unload event timings of the document this one replaced,
+    /// which are only exposed if both documents are same origin.
+    /// <https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-unloadeventstart>
+    pub fn set_previous_document_unload_timings(&self, previous: &Document) {
+        let (start, end) = previous_document_unload_timings(
+            self.origin.same_origin(previous.origin()),
+            previous.fired_unload_event_start.get(),
+            previous.fired_unload_event_end.get(),
+        );
+        self.unload_event_start.set(start);
+        self.unload_event_end.set(end);
+    }
+
     pub fn start_tti(&self) {
         if self.get_interactive_metrics().needs_tti() {
             self.tti_window.borrow_mut().start_window();
@@ -3157,6 +3177,8 @@ impl Document {
             load_event_end: Cell::new(Default::default()),
             unload_event_start: Cell::new(Default::default()),
             unload_event_end: Cell::new(Default::default()),
+            fired_unload_event_start: Cell::new(Default::default()),
+            fired_unload_event_end: Cell::new(Default::default()),
             https_state: Cell::new(HttpsState::None),
             origin: origin,
             referrer: referrer,
@@ -5272,7 +5294,16 @@ fn update_with_current_time_ms(marker: &Cell<u64>) {
     if marker.get() == 0 {
         let time = time::get_time();
         let current_time_ms = time.sec * 1000 + time.nsec as i64 / 1000000;
-        marker.set(current_time_ms as u64);
+        record_milestone(marker, current_time_ms as u64);
+    }
+}
+
+/// Record that a load milestone like `domInteractive` was reached at
+/// `time_ms`, unless it already was. Only the first time counts, so that the
+/// milestones of a load stay in the order they were first reached.
+pub fn record_milestone(marker: &Cell<u64>, time_ms: u64) {
+    if marker.get() == 0 {
+        marker.set(time_ms);
     }
 }
 
@@ -6,6 +6,7 @@ use dom_struct::dom_struct;
 use euclid::default::Transform3D;
 use js::rust::{CustomAutoRooterGuard, HandleObject};
 use js::typedarray::{Float32Array, Float64Array};
+use servo_config::pref;
 
 use crate::dom::bindings::codegen::Bindings::DOMMatrixBinding::{DOMMatrixInit, DOMMatrixMethods};
 use crate::dom::bindings::codegen::Bindings::DOMMatrixReadOnlyBinding::DOMMatrixReadOnlyMethods;
@@ -15,8 +16,10 @@ use crate::dom::bindings::error::Fallible;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::reflect_dom_object_with_proto;
 use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
 use crate::dom::dommatrixreadonly::{
-    dommatrixinit_to_matrix, entries_to_matrix, transform_to_matrix, DOMMatrixReadOnly,
+    angle_argument_in_degrees, clone_matrix, dommatrixinit_to_matrix, entries_to_matrix,
+    transform_to_matrix, validate_scale_origin, DOMMatrixReadOnly,
 };
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::window::Window;
@@ -89,7 +92,8 @@ impl DOMMatrix {
     }
 
     pub fn from_readonly(global: &GlobalScope, ro: &DOMMatrixReadOnly) -> DomRoot<Self> {
-        Self::new(global, ro.is2D(), ro.matrix().clone())
+        let (is2D, matrix) = clone_matrix(ro.is2D(), &ro.matrix());
+        Self::new(global, is2D, matrix)
     }
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrix-fromfloat32array
@@ -350,6 +354,17 @@ impl DOMMatrixMethods for DOMMatrix {
             .and(Ok(DomRoot::from_ref(&self)))
     }
 
+    // Non-standard, see the comment in DOMMatrix.webidl.
+    fn MultiplySelfFromArray(
+        &self,
+        array: CustomAutoRooterGuard<Float32Array>,
+    ) -> Fallible<DomRoot<DOMMatrix>> {
+        let entries: Vec<f64> = array.to_vec().iter().map(|&x| x as f64).collect();
+        self.upcast::<DOMMatrixReadOnly>()
+            .multiply_self_by_entries(&entries)?;
+        Ok(DomRoot::from_ref(&self))
+    }
+
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrix-premultiplyself
     fn PreMultiplySelf(&self, other: &DOMMatrixInit) -> Fallible<DomRoot<DOMMatrix>> {
         // Steps 1-3.
@@ -377,12 +392,17 @@ impl DOMMatrixMethods for DOMMatrix {
         originX: f64,
         originY: f64,
         originZ: f64,
-    ) -> DomRoot<DOMMatrix> {
+    ) -> Fallible<DomRoot<DOMMatrix>> {
+        // Non-standard, see the comment in DOMMatrix.webidl.
+        validate_scale_origin(
+            [originX, originY, originZ],
+            pref!(dom.dommatrix.strict_origins.enabled),
+        )?;
         // Steps 1-6.
         self.upcast::<DOMMatrixReadOnly>()
             .scale_self(scaleX, scaleY, scaleZ, originX, originY, originZ);
         // Step 7.
-        DomRoot::from_ref(&self)
+        Ok(DomRoot::from_ref(&self))
     }
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrix-scale3dself
@@ -392,12 +412,17 @@ impl DOMMatrixMethods for DOMMatrix {
         originX: f64,
         originY: f64,
         originZ: f64,
-    ) -> DomRoot<DOMMatrix> {
+    ) -> Fallible<DomRoot<DOMMatrix>> {
+        // Non-standard, see the comment in DOMMatrix.webidl.
+        validate_scale_origin(
+            [originX, originY, originZ],
+            pref!(dom.dommatrix.strict_origins.enabled),
+        )?;
         // Steps 1-4.
         self.upcast::<DOMMatrixReadOnly>()
             .scale_3d_self(scale, originX, originY, originZ);
         // Step 5.
-        DomRoot::from_ref(&self)
+        Ok(DomRoot::from_ref(&self))
     }
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrix-rotateself
@@ -409,6 +434,23 @@ impl DOMMatrixMethods for DOMMatrix {
         DomRoot::from_ref(&self)
     }
 
+    // Non-standard, see the comment in DOMMatrix.webidl.
+    fn RotateSelfFromAngles(
+        &self,
+        rotX: DOMString,
+        rotY: Option<DOMString>,
+        rotZ: Option<DOMString>,
+    ) -> Fallible<DomRoot<DOMMatrix>> {
+        let rotX = angle_argument_in_degrees(&rotX)?;
+        let rotY = rotY
+            .map(|rotY| angle_argument_in_degrees(&rotY))
+            .transpose()?;
+        let rotZ = rotZ
+            .map(|rotZ| angle_argument_in_degrees(&rotZ))
+            .transpose()?;
+        Ok(self.RotateSelf(rotX, rotY, rotZ))
+    }
+
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrix-rotatefromvectorself
     fn RotateFromVectorSelf(&self, x: f64, y: f64) -> DomRoot<DOMMatrix> {
         // Step 1.
@@ -435,6 +477,11 @@ impl DOMMatrixMethods for DOMMatrix {
         DomRoot::from_ref(&self)
     }
 
+    // Non-standard, see the comment in DOMMatrix.webidl.
+    fn SkewXSelfFromAngle(&self, sx: DOMString) -> Fallible<DomRoot<DOMMatrix>> {
+        Ok(self.SkewXSelf(angle_argument_in_degrees(&sx)?))
+    }
+
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrix-skewyself
     fn SkewYSelf(&self, sy: f64) -> DomRoot<DOMMatrix> {
         // Step 1.
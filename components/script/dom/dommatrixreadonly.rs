@@ -2,22 +2,33 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::hash::Hasher;
 use std::ptr::NonNull;
 use std::{f64, ptr};
 
 use cssparser::{Parser, ParserInput};
 use dom_struct::dom_struct;
-use euclid::default::Transform3D;
+use euclid::default::{Transform2D, Transform3D};
 use euclid::Angle;
+use fnv::FnvHasher;
 use js::jsapi::JSObject;
 use js::rust::{CustomAutoRooterGuard, HandleObject};
-use js::typedarray::{CreateWith, Float32Array, Float64Array};
-use style::parser::ParserContext;
-
-use crate::dom::bindings::cell::{DomRefCell, Ref};
-use crate::dom::bindings::codegen::Bindings::DOMMatrixBinding::{DOMMatrixInit, DOMMatrixMethods};
-use crate::dom::bindings::codegen::Bindings::DOMMatrixReadOnlyBinding::DOMMatrixReadOnlyMethods;
+use js::typedarray::{ArrayBuffer, CreateWith, Float32Array, Float64Array};
+use servo_config::pref;
+use style::parser::{Parse, ParserContext};
+use style::values::animated::transform::decompose_3d_matrix;
+use style::values::computed::transform::Matrix3D;
+use style::values::specified;
+
+use crate::dom::bindings::cell::{DomRefCell, Ref, RefMut};
+use crate::dom::bindings::codegen::Bindings::DOMMatrixBinding::{
+    DOMMatrix2DInit, DOMMatrixInit, DOMMatrixMethods,
+};
+use crate::dom::bindings::codegen::Bindings::DOMMatrixReadOnlyBinding::{
+    DOMMatrixBufferFormat, DOMMatrixReadOnlyMethods,
+};
 use crate::dom::bindings::codegen::Bindings::DOMPointBinding::DOMPointInit;
 use crate::dom::bindings::codegen::UnionTypes::StringOrUnrestrictedDoubleSequence;
 use crate::dom::bindings::error;
@@ -25,6 +36,7 @@ use crate::dom::bindings::error::Fallible;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{reflect_dom_object_with_proto, DomObject, Reflector};
 use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
 use crate::dom::bindings::typedarrays::create_float32_array;
 use crate::dom::dommatrix::DOMMatrix;
 use crate::dom::dompoint::DOMPoint;
@@ -39,6 +51,12 @@ pub struct DOMMatrixReadOnly {
     #[no_trace]
     matrix: DomRefCell<Transform3D<f64>>,
     is2D: Cell<bool>,
+    /// Whether the matrix was mutated since the last `take_dirty()`.
+    dirty: Cell<bool>,
+    /// The rotation quaternion of the matrix, decomposed on first use and
+    /// cleared whenever the matrix is mutated.
+    #[ignore_malloc_size_of = "Arrays are cumbersome"]
+    rotation_quaternion: Cell<Option<[f64; 4]>>,
 }
 
 #[allow(non_snake_case)]
@@ -63,6 +81,8 @@ impl DOMMatrixReadOnly {
             reflector_: Reflector::new(),
             matrix: DomRefCell::new(matrix),
             is2D: Cell::new(is2D),
+            dirty: Cell::new(false),
+            rotation_quaternion: Cell::new(None),
         }
     }
 
@@ -113,84 +133,204 @@ impl DOMMatrixReadOnly {
         self.is2D.get()
     }
 
+    /// Recompute the is2D flag from the matrix's values. The flag is sticky
+    /// per spec, so a matrix that was made 3D stays 3D, and serializes as
+    /// `matrix3d()`, even once its values are 2D again; this normalizes it.
+    // No caller yet: nothing in the tree normalizes a matrix before
+    // serializing it.
+    #[allow(dead_code)]
+    pub fn recompute_is2d(&self) {
+        self.is2D.set(self.matrix.borrow().is_2d());
+    }
+
+    /// The `index`th column of the matrix (`m11` to `m14` for index 0), or
+    /// `None` if `index` is not in `0..4`.
+    pub fn get_column(&self, index: usize) -> Option<[f64; 4]> {
+        matrix_column(&self.matrix.borrow(), index)
+    }
+
+    /// The `index`th row of the matrix (`m11` to `m41` for index 0), or
+    /// `None` if `index` is not in `0..4`.
+    pub fn get_row(&self, index: usize) -> Option<[f64; 4]> {
+        matrix_row(&self.matrix.borrow(), index)
+    }
+
+    /// The components of the matrix in row-major order (`m11`, `m21`, `m31`,
+    /// `m41`, `m12`, ...). This is the transpose of the column-major order of
+    /// `toFloat64Array()` and CSS `matrix3d()`, which both start with `m11`,
+    /// `m12`, `m13`, `m14`.
+    // No caller yet, like from_row_major(): both are for code exchanging
+    // matrices with row-major APIs.
+    #[allow(dead_code)]
+    pub fn to_row_major(&self) -> [f64; 16] {
+        matrix_to_row_major(&self.matrix.borrow())
+    }
+
+    /// Create a 3D matrix from components in row-major order, the transpose
+    /// of what the `sequence<unrestricted double>` constructor expects.
+    #[allow(dead_code)]
+    pub fn from_row_major(global: &GlobalScope, entries: &[f64; 16]) -> DomRoot<Self> {
+        Self::new(global, false, matrix_from_row_major(entries))
+    }
+
+    /// A stable hash of the matrix components, suitable as a cache key for
+    /// deduplicating transforms. Equal matrices hash equally.
+    // No caller yet: no transform cache is keyed on DOMMatrix objects.
+    #[allow(dead_code)]
+    pub fn transform_hash(&self) -> u64 {
+        matrix_hash(&self.matrix.borrow())
+    }
+
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-m11
     pub fn set_m11(&self, value: f64) {
-        self.matrix.borrow_mut().m11 = value;
+        self.matrix_mut().m11 = value;
     }
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-m12
     pub fn set_m12(&self, value: f64) {
-        self.matrix.borrow_mut().m12 = value;
+        self.matrix_mut().m12 = value;
     }
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-m13
     pub fn set_m13(&self, value: f64) {
-        self.matrix.borrow_mut().m13 = value;
+        self.matrix_mut().m13 = value;
+        if value != 0.0 {
+            self.is2D.set(false);
+        }
     }
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-m14
     pub fn set_m14(&self, value: f64) {
-        self.matrix.borrow_mut().m14 = value;
+        self.matrix_mut().m14 = value;
+        if value != 0.0 {
+            self.is2D.set(false);
+        }
     }
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-m21
     pub fn set_m21(&self, value: f64) {
-        self.matrix.borrow_mut().m21 = value;
+        self.matrix_mut().m21 = value;
     }
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-m22
     pub fn set_m22(&self, value: f64) {
-        self.matrix.borrow_mut().m22 = value;
+        self.matrix_mut().m22 = value;
     }
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-m23
     pub fn set_m23(&self, value: f64) {
-        self.matrix.borrow_mut().m23 = value;
+        self.matrix_mut().m23 = value;
+        if value != 0.0 {
+            self.is2D.set(false);
+        }
     }
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-m24
     pub fn set_m24(&self, value: f64) {
-        self.matrix.borrow_mut().m24 = value;
+        self.matrix_mut().m24 = value;
+        if value != 0.0 {
+            self.is2D.set(false);
+        }
     }
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-m31
     pub fn set_m31(&self, value: f64) {
-        self.matrix.borrow_mut().m31 = value;
+        self.matrix_mut().m31 = value;
+        if value != 0.0 {
+            self.is2D.set(false);
+        }
     }
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-m32
     pub fn set_m32(&self, value: f64) {
-        self.matrix.borrow_mut().m32 = value;
+        self.matrix_mut().m32 = value;
+        if value != 0.0 {
+            self.is2D.set(false);
+        }
     }
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-m33
     pub fn set_m33(&self, value: f64) {
-        self.matrix.borrow_mut().m33 = value;
+        self.matrix_mut().m33 = value;
+        if value != 1.0 {
+            self.is2D.set(false);
+        }
     }
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-m34
     pub fn set_m34(&self, value: f64) {
-        self.matrix.borrow_mut().m34 = value;
+        self.matrix_mut().m34 = value;
+        if value != 0.0 {
+            self.is2D.set(false);
+        }
     }
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-m41
     pub fn set_m41(&self, value: f64) {
-        self.matrix.borrow_mut().m41 = value;
+        self.matrix_mut().m41 = value;
     }
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-m42
     pub fn set_m42(&self, value: f64) {
-        self.matrix.borrow_mut().m42 = value;
+        self.matrix_mut().m42 = value;
     }
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-m43
     pub fn set_m43(&self, value: f64) {
-        self.matrix.borrow_mut().m43 = value;
+        self.matrix_mut().m43 = value;
+        if value != 0.0 {
+            self.is2D.set(false);
+        }
     }
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-m44
     pub fn set_m44(&self, value: f64) {
-        self.matrix.borrow_mut().m44 = value;
+        self.matrix_mut().m44 = value;
+        if value != 1.0 {
+            self.is2D.set(false);
+        }
+    }
+
+    /// Borrow the matrix to mutate it, marking it dirty and dropping the
+    /// state derived from it.
+    fn matrix_mut(&self) -> RefMut<Transform3D<f64>> {
+        self.dirty.set(true);
+        self.rotation_quaternion.set(None);
+        self.matrix.borrow_mut()
+    }
+
+    /// The `[x, y, z, w]` rotation quaternion of the matrix, or `None` if it
+    /// cannot be decomposed. Animation code reads it every frame, so it is
+    /// only decomposed again once the matrix changed.
+    pub fn rotation_quaternion(&self) -> Option<[f64; 4]> {
+        cached_rotation_quaternion(&self.rotation_quaternion, &self.matrix.borrow())
+    }
+
+    /// Whether the matrix was mutated since the last call, clearing the flag.
+    /// Lets consumers that derive state from the matrix, like a cached GPU
+    /// transform, know when to recompute it.
+    // No consumer polls it yet: canvas and animation code don't keep
+    // DOMMatrix objects around to derive state from.
+    #[allow(dead_code)]
+    pub fn take_dirty(&self) -> bool {
+        self.dirty.replace(false)
+    }
+
+    /// Set the `m{row}{col}` component of the matrix, with `row` and `col`
+    /// both in `1..=4`, clearing is2D like the matching `m*` setter would.
+    /// Fails with an `IndexSizeError`, leaving the matrix untouched and not
+    /// dirty, if either is out of range.
+    // No caller in the tree yet: this is for code that picks the component to
+    // set at run time, rather than through one of the named setters.
+    #[allow(dead_code)]
+    pub fn set_component(&self, row: usize, col: usize, value: f64) -> Fallible<()> {
+        let mut matrix = *self.matrix.borrow();
+        set_matrix_component(&mut matrix, row, col, value)?;
+        *self.matrix_mut() = matrix;
+        if !component_keeps_2d(row, col, value) {
+            self.is2D.set(false);
+        }
+        Ok(())
     }
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrix-multiplyself
@@ -198,8 +338,8 @@ impl DOMMatrixReadOnly {
         // Step 1.
         dommatrixinit_to_matrix(&other).map(|(is2D, other_matrix)| {
             // Step 2.
-            let mut matrix = self.matrix.borrow_mut();
-            *matrix = other_matrix.then(&matrix);
+            let mut matrix = self.matrix_mut();
+            *matrix = post_multiply(&matrix, self.is2D.get(), &other_matrix, is2D);
             // Step 3.
             if !is2D {
                 self.is2D.set(false);
@@ -208,13 +348,40 @@ impl DOMMatrixReadOnly {
         })
     }
 
+    /// Like `multiply_self()`, but by another matrix object rather than an
+    /// init dict. `other` may be `self`: its matrix is copied out before this
+    /// matrix is mutably borrowed, so multiplying a matrix by itself never
+    /// borrows `self.matrix` twice.
+    // No caller yet: the bindings only ever pass a DOMMatrixInit.
+    #[allow(dead_code)]
+    pub fn multiply_self_by(&self, other: &DOMMatrixReadOnly) {
+        let (other_is_2d, other_matrix) = (other.is2D(), *other.matrix());
+        let mut matrix = self.matrix_mut();
+        *matrix = post_multiply(&matrix, self.is2D.get(), &other_matrix, other_is_2d);
+        if !other_is_2d {
+            self.is2D.set(false);
+        }
+    }
+
+    /// Like `multiply_self()`, but by the 6 or 16 entries of a matrix as the
+    /// sequence constructor reads them, without building a `DOMMatrix` first.
+    pub fn multiply_self_by_entries(&self, entries: &[f64]) -> Fallible<()> {
+        let (other_is_2d, other_matrix) = entries_to_matrix(entries)?;
+        let mut matrix = self.matrix_mut();
+        *matrix = post_multiply(&matrix, self.is2D.get(), &other_matrix, other_is_2d);
+        if !other_is_2d {
+            self.is2D.set(false);
+        }
+        Ok(())
+    }
+
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrix-premultiplyself
     pub fn pre_multiply_self(&self, other: &DOMMatrixInit) -> Fallible<()> {
         // Step 1.
         dommatrixinit_to_matrix(&other).map(|(is2D, other_matrix)| {
             // Step 2.
-            let mut matrix = self.matrix.borrow_mut();
-            *matrix = matrix.then(&other_matrix);
+            let mut matrix = self.matrix_mut();
+            *matrix = pre_multiply(&matrix, self.is2D.get(), &other_matrix, is2D);
             // Step 3.
             if !is2D {
                 self.is2D.set(false);
@@ -225,43 +392,39 @@ impl DOMMatrixReadOnly {
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrix-translateself
     pub fn translate_self(&self, tx: f64, ty: f64, tz: f64) {
-        // Step 1.
-        let translation = Transform3D::translation(tx, ty, tz);
-        let mut matrix = self.matrix.borrow_mut();
-        *matrix = translation.then(&matrix);
-        // Step 2.
-        if tz != 0.0 {
-            self.is2D.set(false);
-        }
+        // Steps 1 and 2.
+        let (is2D, matrix) = translated_matrix(self.is2D.get(), &self.matrix.borrow(), tx, ty, tz);
+        *self.matrix_mut() = matrix;
+        self.is2D.set(is2D);
         // Step 3 in DOMMatrix.TranslateSelf
     }
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrix-scaleself
+    //
+    // The origin is an unrestricted double, so a NaN origin is not rejected
+    // here: the translations of steps 1 and 5 turn the matrix into NaNs.
     pub fn scale_self(
         &self,
         scaleX: f64,
         scaleY: Option<f64>,
         scaleZ: f64,
-        mut originX: f64,
-        mut originY: f64,
-        mut originZ: f64,
+        originX: f64,
+        originY: f64,
+        originZ: f64,
     ) {
-        // Step 1.
-        self.translate_self(originX, originY, originZ);
-        // Step 2.
-        let scaleY = scaleY.unwrap_or(scaleX);
-        // Step 3.
+        // Steps 1-5.
+        let ops = MatrixOp::scale(
+            scaleX,
+            scaleY.unwrap_or(scaleX),
+            scaleZ,
+            originX,
+            originY,
+            originZ,
+        );
         {
-            let scale3D = Transform3D::scale(scaleX, scaleY, scaleZ);
-            let mut matrix = self.matrix.borrow_mut();
-            *matrix = scale3D.then(&matrix);
+            let mut matrix = self.matrix_mut();
+            *matrix = compose_matrix(&matrix, &ops);
         }
-        // Step 4.
-        originX = -originX;
-        originY = -originY;
-        originZ = -originZ;
-        // Step 5.
-        self.translate_self(originX, originY, originZ);
         // Step 6.
         if scaleZ != 1.0 || originZ != 0.0 {
             self.is2D.set(false);
@@ -271,57 +434,31 @@ impl DOMMatrixReadOnly {
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrix-scale3dself
     pub fn scale_3d_self(&self, scale: f64, originX: f64, originY: f64, originZ: f64) {
-        // Step 1.
-        self.translate_self(originX, originY, originZ);
-        // Step 2.
+        // Steps 1-3.
+        let ops = MatrixOp::scale_3d(scale, originX, originY, originZ);
         {
-            let scale3D = Transform3D::scale(scale, scale, scale);
-            let mut matrix = self.matrix.borrow_mut();
-            *matrix = scale3D.then(&matrix);
+            let mut matrix = self.matrix_mut();
+            *matrix = compose_matrix(&matrix, &ops);
         }
-        // Step 3.
-        self.translate_self(-originX, -originY, -originZ);
-        // Step 4.
-        if scale != 1.0 {
+        // Step 4, along with the translateSelf() of steps 1 and 3, which
+        // makes the matrix 3D for a non-zero originZ.
+        if scale != 1.0 || originZ != 0.0 {
             self.is2D.set(false);
         }
         // Step 5 in DOMMatrix.Scale3dSelf
     }
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrix-rotateself
-    pub fn rotate_self(&self, mut rotX: f64, mut rotY: Option<f64>, mut rotZ: Option<f64>) {
-        // Step 1.
-        if rotY.is_none() && rotZ.is_none() {
-            rotZ = Some(rotX);
-            rotX = 0.0;
-            rotY = Some(0.0);
-        }
-        // Step 2.
-        let rotY = rotY.unwrap_or(0.0);
-        // Step 3.
-        let rotZ = rotZ.unwrap_or(0.0);
+    pub fn rotate_self(&self, rotX: f64, rotY: Option<f64>, rotZ: Option<f64>) {
+        // Steps 1-3.
+        let rotation = MatrixOp::rotate(rotX, rotY, rotZ);
         // Step 4.
-        if rotX != 0.0 || rotY != 0.0 {
+        if !rotation.preserves_2d() {
             self.is2D.set(false);
         }
-        if rotZ != 0.0 {
-            // Step 5.
-            let rotation = Transform3D::rotation(0.0, 0.0, 1.0, Angle::radians(rotZ.to_radians()));
-            let mut matrix = self.matrix.borrow_mut();
-            *matrix = rotation.then(&matrix);
-        }
-        if rotY != 0.0 {
-            // Step 6.
-            let rotation = Transform3D::rotation(0.0, 1.0, 0.0, Angle::radians(rotY.to_radians()));
-            let mut matrix = self.matrix.borrow_mut();
-            *matrix = rotation.then(&matrix);
-        }
-        if rotX != 0.0 {
-            // Step 7.
-            let rotation = Transform3D::rotation(1.0, 0.0, 0.0, Angle::radians(rotX.to_radians()));
-            let mut matrix = self.matrix.borrow_mut();
-            *matrix = rotation.then(&matrix);
-        }
+        // Steps 5-7.
+        let mut matrix = self.matrix_mut();
+        *matrix = compose_matrix(&matrix, &[rotation]);
         // Step 8 in DOMMatrix.RotateSelf
     }
 
@@ -332,7 +469,7 @@ impl DOMMatrixReadOnly {
             // Step 1.
             let rotZ = Angle::radians(f64::atan2(y, x));
             let rotation = Transform3D::rotation(0.0, 0.0, 1.0, rotZ);
-            let mut matrix = self.matrix.borrow_mut();
+            let mut matrix = self.matrix_mut();
             *matrix = rotation.then(&matrix);
         }
         // Step 2 in DOMMatrix.RotateFromVectorSelf
@@ -340,15 +477,12 @@ impl DOMMatrixReadOnly {
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrix-rotateaxisangleself
     pub fn rotate_axis_angle_self(&self, x: f64, y: f64, z: f64, angle: f64) {
+        let rotation = MatrixOp::RotateAxisAngle(x, y, z, angle);
         // Step 1.
-        let (norm_x, norm_y, norm_z) = normalize_point(x, y, z);
-        // Beware: pass negated value until https://github.com/servo/euclid/issues/354
-        let rotation =
-            Transform3D::rotation(norm_x, norm_y, norm_z, Angle::radians(angle.to_radians()));
-        let mut matrix = self.matrix.borrow_mut();
-        *matrix = rotation.then(&matrix);
+        let mut matrix = self.matrix_mut();
+        *matrix = compose_matrix(&matrix, &[rotation]);
         // Step 2.
-        if x != 0.0 || y != 0.0 {
+        if !rotation.preserves_2d() {
             self.is2D.set(false);
         }
         // Step 3 in DOMMatrix.RotateAxisAngleSelf
@@ -356,51 +490,51 @@ impl DOMMatrixReadOnly {
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrix-skewxself
     pub fn skew_x_self(&self, sx: f64) {
-        // Step 1.
-        let skew = Transform3D::skew(Angle::radians(sx.to_radians()), Angle::radians(0.0));
-        let mut matrix = self.matrix.borrow_mut();
-        *matrix = skew.then(&matrix);
+        // Step 1. A skew leaves is2D as it was.
+        let mut matrix = self.matrix_mut();
+        *matrix = compose_matrix(&matrix, &[MatrixOp::SkewX(sx)]);
         // Step 2 in DOMMatrix.SkewXSelf
     }
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrix-skewyself
     pub fn skew_y_self(&self, sy: f64) {
-        // Step 1.
-        let skew = Transform3D::skew(Angle::radians(0.0), Angle::radians(sy.to_radians()));
-        let mut matrix = self.matrix.borrow_mut();
-        *matrix = skew.then(&matrix);
+        // Step 1. A skew leaves is2D as it was.
+        let mut matrix = self.matrix_mut();
+        *matrix = compose_matrix(&matrix, &[MatrixOp::SkewY(sy)]);
         // Step 2 in DOMMatrix.SkewYSelf
     }
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrix-invertself
     pub fn invert_self(&self) {
-        let mut matrix = self.matrix.borrow_mut();
-        // Step 1.
-        *matrix = matrix.inverse().unwrap_or_else(|| {
-            // Step 2.
-            self.is2D.set(false);
-            Transform3D::new(
-                f64::NAN,
-                f64::NAN,
-                f64::NAN,
-                f64::NAN,
-                f64::NAN,
-                f64::NAN,
-                f64::NAN,
-                f64::NAN,
-                f64::NAN,
-                f64::NAN,
-                f64::NAN,
-                f64::NAN,
-                f64::NAN,
-                f64::NAN,
-                f64::NAN,
-                f64::NAN,
-            )
-        })
+        let mut matrix = self.matrix_mut();
+        // Steps 1-2.
+        let (is2D, inverse) = invert_matrix(&matrix, self.is2D.get());
+        *matrix = inverse;
+        self.is2D.set(is2D);
         // Step 3 in DOMMatrix.InvertSelf
     }
 
+    /// The `a`, `b`, `c`, `d`, `e` and `f` components of this matrix, in the
+    /// order taken by `CanvasRenderingContext2D.setTransform()`. Fails with an
+    /// `InvalidStateError` if this is not a 2D matrix, rather than silently
+    /// dropping its 3D components.
+    pub fn to_2d_components(&self) -> Fallible<[f64; 6]> {
+        matrix_2d_components(&self.matrix.borrow(), self.is2D())
+    }
+
+    /// Serializing a matrix fails with an `InvalidStateError` if any of its
+    /// components is not finite.
+    pub fn ensure_finite(&self) -> Fallible<()> {
+        ensure_matrix_finite(&self.matrix.borrow())
+    }
+
+    /// Apply `ops` to a copy of this matrix, in the same order and with the
+    /// same semantics as the corresponding chain of `DOMMatrix` self methods,
+    /// without allocating a reflector for each intermediate result.
+    pub fn compose_into(&self, ops: &[MatrixOp]) -> Transform3D<f64> {
+        compose_matrix(&self.matrix.borrow(), ops)
+    }
+
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-fromfloat32array
     #[allow(unsafe_code)]
     pub fn FromFloat32Array(
@@ -428,6 +562,53 @@ impl DOMMatrixReadOnly {
             Some(StringOrUnrestrictedDoubleSequence::UnrestrictedDoubleSequence(vec)),
         )
     }
+
+    // Non-standard, see the comment in DOMMatrixReadOnly.webidl.
+    pub fn FromTransform(
+        global: &GlobalScope,
+        transform: DOMString,
+        origin_x: f64,
+        origin_y: f64,
+    ) -> Fallible<DomRoot<DOMMatrixReadOnly>> {
+        transform_to_matrix_with_origin(transform.to_string(), origin_x, origin_y)
+            .map(|(is2D, matrix)| Self::new(global, is2D, matrix))
+    }
+
+    // Non-standard, see the comment in DOMMatrixReadOnly.webidl.
+    #[allow(unsafe_code)]
+    pub fn FromArrayBuffer(
+        global: &GlobalScope,
+        buffer: CustomAutoRooterGuard<ArrayBuffer>,
+        byte_offset: u32,
+        format: DOMMatrixBufferFormat,
+    ) -> Fallible<DomRoot<DOMMatrixReadOnly>> {
+        let bytes = buffer.to_vec();
+        let vec = matrix_entries_from_bytes(&bytes, byte_offset as usize, format)?;
+        DOMMatrixReadOnly::Constructor(
+            global,
+            None,
+            Some(StringOrUnrestrictedDoubleSequence::UnrestrictedDoubleSequence(vec)),
+        )
+    }
+
+    // Non-standard, see the comment in DOMMatrixReadOnly.webidl.
+    pub fn FromRows(
+        global: &GlobalScope,
+        rows: Vec<Vec<f64>>,
+    ) -> Fallible<DomRoot<DOMMatrixReadOnly>> {
+        matrix_from_rows(&rows).map(|(is2D, matrix)| Self::new(global, is2D, matrix))
+    }
+
+    // Non-standard, see the comment in DOMMatrixReadOnly.webidl.
+    pub fn FromQuaternion(
+        global: &GlobalScope,
+        x: f64,
+        y: f64,
+        z: f64,
+        w: f64,
+    ) -> DomRoot<DOMMatrix> {
+        DOMMatrix::new(global, false, quaternion_to_matrix(x, y, z, w))
+    }
 }
 
 #[allow(non_snake_case)]
@@ -582,25 +763,46 @@ impl DOMMatrixReadOnlyMethods for DOMMatrixReadOnly {
         originX: f64,
         originY: f64,
         originZ: f64,
-    ) -> DomRoot<DOMMatrix> {
+    ) -> Fallible<DomRoot<DOMMatrix>> {
         DOMMatrix::from_readonly(&self.global(), self)
             .ScaleSelf(scaleX, scaleY, scaleZ, originX, originY, originZ)
     }
 
     // https://drafts.fxtf.org/geometry/#dom-dommatrixreadonly-scalenonuniform
     fn ScaleNonUniform(&self, scaleX: f64, scaleY: f64) -> DomRoot<DOMMatrix> {
-        DOMMatrix::from_readonly(&self.global(), self).ScaleSelf(
-            scaleX,
-            Some(scaleY),
-            1.0,
-            0.0,
-            0.0,
-            0.0,
-        )
+        let matrix = DOMMatrix::from_readonly(&self.global(), self);
+        matrix
+            .upcast::<DOMMatrixReadOnly>()
+            .scale_self(scaleX, Some(scaleY), 1.0, 0.0, 0.0, 0.0);
+        matrix
+    }
+
+    // Non-standard, see the comment in DOMMatrixReadOnly.webidl.
+    fn Scale2d(
+        &self,
+        scaleX: f64,
+        scaleY: Option<f64>,
+        originX: f64,
+        originY: f64,
+    ) -> Fallible<DomRoot<DOMMatrix>> {
+        validate_scale_origin(
+            [originX, originY, 0.0],
+            pref!(dom.dommatrix.strict_origins.enabled),
+        )?;
+        let is2D = self.is2D.get();
+        let ops = MatrixOp::scale_2d(scaleX, scaleY.unwrap_or(scaleX), originX, originY);
+        let matrix = self.compose_into(&ops);
+        Ok(DOMMatrix::new(&self.global(), is2D, matrix))
     }
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-scale3d
-    fn Scale3d(&self, scale: f64, originX: f64, originY: f64, originZ: f64) -> DomRoot<DOMMatrix> {
+    fn Scale3d(
+        &self,
+        scale: f64,
+        originX: f64,
+        originY: f64,
+        originZ: f64,
+    ) -> Fallible<DomRoot<DOMMatrix>> {
         DOMMatrix::from_readonly(&self.global(), self).Scale3dSelf(scale, originX, originY, originZ)
     }
 
@@ -634,23 +836,23 @@ impl DOMMatrixReadOnlyMethods for DOMMatrixReadOnly {
         DOMMatrix::from_readonly(&self.global(), self).MultiplySelf(&other)
     }
 
+    // Non-standard, see the comment in DOMMatrixReadOnly.webidl.
+    fn MultiplyAll(&self, matrices: Vec<DOMMatrixInit>) -> Fallible<DomRoot<DOMMatrix>> {
+        let (is2D, matrix) = multiply_all(self.is2D.get(), &self.matrix.borrow(), &matrices)?;
+        Ok(DOMMatrix::new(&self.global(), is2D, matrix))
+    }
+
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-flipx
     fn FlipX(&self) -> DomRoot<DOMMatrix> {
         let is2D = self.is2D.get();
-        let flip = Transform3D::new(
-            -1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0,
-        );
-        let matrix = flip.then(&self.matrix.borrow());
+        let matrix = self.compose_into(&[MatrixOp::FlipX]);
         DOMMatrix::new(&self.global(), is2D, matrix)
     }
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-flipy
     fn FlipY(&self) -> DomRoot<DOMMatrix> {
         let is2D = self.is2D.get();
-        let flip = Transform3D::new(
-            1.0, 0.0, 0.0, 0.0, 0.0, -1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0,
-        );
-        let matrix = flip.then(&self.matrix.borrow());
+        let matrix = self.compose_into(&[MatrixOp::FlipY]);
         DOMMatrix::new(&self.global(), is2D, matrix)
     }
 
@@ -659,6 +861,17 @@ impl DOMMatrixReadOnlyMethods for DOMMatrixReadOnly {
         DOMMatrix::from_readonly(&self.global(), self).InvertSelf()
     }
 
+    // Non-standard, see the comment in DOMMatrixReadOnly.webidl.
+    fn Clone(&self) -> DomRoot<DOMMatrix> {
+        let (is2D, matrix) = clone_matrix(self.is2D(), &self.matrix());
+        DOMMatrix::new(&self.global(), is2D, matrix)
+    }
+
+    // Non-standard, see the comment in DOMMatrixReadOnly.webidl.
+    fn IsEqual(&self, other: &DOMMatrixInit) -> Fallible<bool> {
+        matrix_equals(self.is2D.get(), &self.matrix.borrow(), other)
+    }
+
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-transformpoint
     fn TransformPoint(&self, point: &DOMPointInit) -> DomRoot<DOMPoint> {
         // Euclid always normalizes the homogeneous coordinate which is usually the right
@@ -667,15 +880,28 @@ impl DOMMatrixReadOnlyMethods for DOMMatrixReadOnly {
         // correct in the context of geometry computations).
         // Since this is the only place where this is needed, better implement it here
         // than in euclid (which does not have a notion of 4d points).
-        let mat = self.matrix.borrow();
-        let x = point.x * mat.m11 + point.y * mat.m21 + point.z * mat.m31 + point.w * mat.m41;
-        let y = point.x * mat.m12 + point.y * mat.m22 + point.z * mat.m32 + point.w * mat.m42;
-        let z = point.x * mat.m13 + point.y * mat.m23 + point.z * mat.m33 + point.w * mat.m43;
-        let w = point.x * mat.m14 + point.y * mat.m24 + point.z * mat.m34 + point.w * mat.m44;
+        let [x, y, z, w] = transform_homogeneous_point(
+            &self.matrix.borrow(),
+            [point.x, point.y, point.z, point.w],
+            /* perspective_divide */ false,
+        );
 
         DOMPoint::new(&self.global(), x, y, z, w)
     }
 
+    // Non-standard, see the comment in DOMMatrixReadOnly.webidl.
+    fn TransformPoints(&self, points: Vec<DOMPointInit>) -> Vec<DomRoot<DOMPoint>> {
+        let points: Vec<[f64; 4]> = points
+            .iter()
+            .map(|point| [point.x, point.y, point.z, point.w])
+            .collect();
+        let global = self.global();
+        transform_points(&self.matrix.borrow(), &points)
+            .into_iter()
+            .map(|[x, y, z, w]| DOMPoint::new(&global, x, y, z, w))
+            .collect()
+    }
+
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-tofloat32array
     fn ToFloat32Array(&self, cx: JSContext) -> Float32Array {
         let vec: Vec<f32> = self
@@ -691,17 +917,679 @@ impl DOMMatrixReadOnlyMethods for DOMMatrixReadOnly {
     }
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-tofloat64array
-    #[allow(unsafe_code)]
     fn ToFloat64Array(&self, cx: JSContext) -> NonNull<JSObject> {
-        let arr = self.matrix.borrow().to_array();
-        unsafe {
-            rooted!(in (*cx) let mut array = ptr::null_mut::<JSObject>());
-            let _ = Float64Array::create(*cx, CreateWith::Slice(&arr), array.handle_mut()).unwrap();
-            NonNull::new_unchecked(array.get())
+        create_float64_array(cx, &self.matrix.borrow().to_array())
+    }
+
+    // Non-standard, see the comment in DOMMatrixReadOnly.webidl.
+    fn GetColumn(&self, cx: JSContext, index: u32) -> Fallible<NonNull<JSObject>> {
+        let column = self
+            .get_column(index as usize)
+            .ok_or(error::Error::IndexSize)?;
+        Ok(create_float64_array(cx, &column))
+    }
+
+    // Non-standard, see the comment in DOMMatrixReadOnly.webidl.
+    fn GetRow(&self, cx: JSContext, index: u32) -> Fallible<NonNull<JSObject>> {
+        let row = self
+            .get_row(index as usize)
+            .ok_or(error::Error::IndexSize)?;
+        Ok(create_float64_array(cx, &row))
+    }
+
+    // Non-standard, see the comment in DOMMatrixReadOnly.webidl.
+    fn To2DComponents(&self) -> Fallible<Vec<f64>> {
+        self.to_2d_components()
+            .map(|components| components.to_vec())
+    }
+
+    // Non-standard, see the comment in DOMMatrixReadOnly.webidl.
+    fn GetTranslate(&self) -> Fallible<DomRoot<DOMPoint>> {
+        let decomposed =
+            decompose_matrix(&self.matrix.borrow()).ok_or(error::Error::InvalidState)?;
+        let [x, y, z] = decomposed.translate;
+        Ok(DOMPoint::new(&self.global(), x, y, z, 1.))
+    }
+
+    // Non-standard, see the comment in DOMMatrixReadOnly.webidl.
+    fn GetScale(&self) -> Fallible<DomRoot<DOMPoint>> {
+        let decomposed =
+            decompose_matrix(&self.matrix.borrow()).ok_or(error::Error::InvalidState)?;
+        let [x, y, z] = decomposed.scale;
+        Ok(DOMPoint::new(&self.global(), x, y, z, 1.))
+    }
+
+    // Non-standard, see the comment in DOMMatrixReadOnly.webidl.
+    fn GetRotationQuaternion(&self) -> Fallible<Vec<f64>> {
+        let quaternion = self
+            .rotation_quaternion()
+            .ok_or(error::Error::InvalidState)?;
+        Ok(quaternion.to_vec())
+    }
+
+    // https://drafts.fxtf.org/geometry-1/#dommatrixreadonly-stringification-behavior
+    fn Stringifier(&self) -> Fallible<DOMString> {
+        self.ensure_finite()?;
+        serialize_matrix(&self.matrix.borrow(), self.is2D()).map(DOMString::from)
+    }
+
+    // Non-standard, see the comment in DOMMatrixReadOnly.webidl.
+    fn ToMatrix3dString(&self) -> Fallible<DOMString> {
+        self.ensure_finite()?;
+        serialize_matrix(&self.matrix.borrow(), /* is_2d */ false).map(DOMString::from)
+    }
+}
+
+#[allow(unsafe_code)]
+fn create_float64_array(cx: JSContext, values: &[f64]) -> NonNull<JSObject> {
+    unsafe {
+        rooted!(in (*cx) let mut array = ptr::null_mut::<JSObject>());
+        let _ = Float64Array::create(*cx, CreateWith::Slice(values), array.handle_mut()).unwrap();
+        NonNull::new_unchecked(array.get())
+    }
+}
+
+/// A single step of a transform composed by `DOMMatrixReadOnly::compose_into`.
+#[derive(Clone, Copy, Debug)]
+pub enum MatrixOp {
+    /// Equivalent to `translateSelf(tx, ty, tz)`.
+    Translate(f64, f64, f64),
+    /// Equivalent to `scaleSelf(scaleX, scaleY, scaleZ)` about the origin.
+    Scale(f64, f64, f64),
+    /// Equivalent to `rotateSelf(rotX, rotY, rotZ)`, with angles in degrees.
+    Rotate(f64, f64, f64),
+    /// Equivalent to `rotateAxisAngleSelf(x, y, z, angle)`, with the angle in degrees.
+    RotateAxisAngle(f64, f64, f64, f64),
+    /// Equivalent to `skewXSelf(sx)`, with the angle in degrees.
+    SkewX(f64),
+    /// Equivalent to `skewYSelf(sy)`, with the angle in degrees.
+    SkewY(f64),
+    /// Equivalent to `flipX()`.
+    FlipX,
+    /// Equivalent to `flipY()`.
+    FlipY,
+    /// Equivalent to `multiplySelf(other)`.
+    Multiply(Transform3D<f64>),
+}
+
+impl MatrixOp {
+    /// The rotation applied by `rotateSelf(rotX, rotY, rotZ)`. With a single
+    /// argument it is a rotation about the Z axis, which keeps a 2D matrix
+    /// 2D; any non-zero X or Y rotation makes it 3D.
+    /// <https://drafts.fxtf.org/geometry-1/#dom-dommatrix-rotateself>
+    #[allow(non_snake_case)]
+    pub fn rotate(mut rotX: f64, mut rotY: Option<f64>, mut rotZ: Option<f64>) -> MatrixOp {
+        // Step 1.
+        if rotY.is_none() && rotZ.is_none() {
+            rotZ = Some(rotX);
+            rotX = 0.0;
+            rotY = Some(0.0);
+        }
+        // Step 2.
+        let rotY = rotY.unwrap_or(0.0);
+        // Step 3.
+        let rotZ = rotZ.unwrap_or(0.0);
+        MatrixOp::Rotate(rotX, rotY, rotZ)
+    }
+
+    /// The steps of `scaleSelf(sx, sy, sz, origin_x, origin_y, origin_z)`:
+    /// translate to the origin, scale, and translate back.
+    pub fn scale(
+        sx: f64,
+        sy: f64,
+        sz: f64,
+        origin_x: f64,
+        origin_y: f64,
+        origin_z: f64,
+    ) -> [MatrixOp; 3] {
+        [
+            MatrixOp::Translate(origin_x, origin_y, origin_z),
+            MatrixOp::Scale(sx, sy, sz),
+            MatrixOp::Translate(-origin_x, -origin_y, -origin_z),
+        ]
+    }
+
+    /// The steps of `scale3dSelf(scale, origin_x, origin_y, origin_z)`.
+    pub fn scale_3d(scale: f64, origin_x: f64, origin_y: f64, origin_z: f64) -> [MatrixOp; 3] {
+        MatrixOp::scale(scale, scale, scale, origin_x, origin_y, origin_z)
+    }
+
+    /// A 2D scale by `(sx, sy)` about `(origin_x, origin_y)`: the steps of
+    /// `scaleSelf(sx, sy, 1, origin_x, origin_y, 0)`, all of which keep a 2D
+    /// matrix 2D.
+    pub fn scale_2d(sx: f64, sy: f64, origin_x: f64, origin_y: f64) -> [MatrixOp; 3] {
+        [
+            MatrixOp::Translate(origin_x, origin_y, 0.0),
+            MatrixOp::Scale(sx, sy, 1.0),
+            MatrixOp::Translate(-origin_x, -origin_y, 0.0),
+        ]
+    }
+
+    /// Whether applying this operation leaves a 2D matrix 2D.
+    pub fn preserves_2d(&self) -> bool {
+        match *self {
+            MatrixOp::Translate(_, _, tz) => tz == 0.0,
+            MatrixOp::Scale(_, _, sz) => sz == 1.0,
+            MatrixOp::Rotate(rot_x, rot_y, _) => rot_x == 0.0 && rot_y == 0.0,
+            MatrixOp::RotateAxisAngle(x, y, _, _) => x == 0.0 && y == 0.0,
+            // A skew only ever touches m21 or m12, so never makes a matrix 3D.
+            MatrixOp::SkewX(_) | MatrixOp::SkewY(_) => true,
+            MatrixOp::FlipX | MatrixOp::FlipY => true,
+            MatrixOp::Multiply(other) => other.is_2d(),
         }
     }
 }
 
+/// Transform the homogeneous `point` by `matrix`, as `transformPoint()` does.
+/// With `perspective_divide`, the result is projected back to `w = 1` by
+/// dividing by its `w`, unless that is 0; `transformPoint()` itself never
+/// divides.
+pub fn transform_homogeneous_point(
+    matrix: &Transform3D<f64>,
+    point: [f64; 4],
+    perspective_divide: bool,
+) -> [f64; 4] {
+    let [px, py, pz, pw] = point;
+    let m = matrix;
+    let x = px * m.m11 + py * m.m21 + pz * m.m31 + pw * m.m41;
+    let y = px * m.m12 + py * m.m22 + pz * m.m32 + pw * m.m42;
+    let z = px * m.m13 + py * m.m23 + pz * m.m33 + pw * m.m43;
+    let w = px * m.m14 + py * m.m24 + pz * m.m34 + pw * m.m44;
+    if perspective_divide && w != 0.0 {
+        [x / w, y / w, z / w, 1.0]
+    } else {
+        [x, y, z, w]
+    }
+}
+
+/// `points` transformed by `matrix` as `transformPoint()` would transform each
+/// of them, with the matrix borrowed once for the whole batch.
+pub fn transform_points(matrix: &Transform3D<f64>, points: &[[f64; 4]]) -> Vec<[f64; 4]> {
+    points
+        .iter()
+        .map(|point| {
+            transform_homogeneous_point(matrix, *point, /* perspective_divide */ false)
+        })
+        .collect()
+}
+
+/// Post-multiply `matrix` by each of `ops` in turn.
+pub fn compose_matrix(matrix: &Transform3D<f64>, ops: &[MatrixOp]) -> Transform3D<f64> {
+    ops.iter().fold(*matrix, |matrix, op| match *op {
+        MatrixOp::Translate(tx, ty, tz) => Transform3D::translation(tx, ty, tz).then(&matrix),
+        MatrixOp::Scale(sx, sy, sz) => Transform3D::scale(sx, sy, sz).then(&matrix),
+        MatrixOp::Rotate(rot_x, rot_y, rot_z) => {
+            // Same order as rotateSelf: Z, then Y, then X.
+            let mut matrix = matrix;
+            if rot_z != 0.0 {
+                matrix = rotation(0.0, 0.0, 1.0, rot_z).then(&matrix);
+            }
+            if rot_y != 0.0 {
+                matrix = rotation(0.0, 1.0, 0.0, rot_y).then(&matrix);
+            }
+            if rot_x != 0.0 {
+                matrix = rotation(1.0, 0.0, 0.0, rot_x).then(&matrix);
+            }
+            matrix
+        },
+        MatrixOp::RotateAxisAngle(x, y, z, angle) => {
+            let (norm_x, norm_y, norm_z) = normalize_point(x, y, z);
+            // Beware: pass negated value until https://github.com/servo/euclid/issues/354
+            rotation(norm_x, norm_y, norm_z, angle).then(&matrix)
+        },
+        MatrixOp::SkewX(sx) => {
+            Transform3D::skew(Angle::radians(sx.to_radians()), Angle::radians(0.0)).then(&matrix)
+        },
+        MatrixOp::SkewY(sy) => {
+            Transform3D::skew(Angle::radians(0.0), Angle::radians(sy.to_radians())).then(&matrix)
+        },
+        // Post-multiplying by [-1, 0, 0, 1, 0, 0] or [1, 0, 0, -1, 0, 0].
+        MatrixOp::FlipX => Transform3D::scale(-1.0, 1.0, 1.0).then(&matrix),
+        MatrixOp::FlipY => Transform3D::scale(1.0, -1.0, 1.0).then(&matrix),
+        MatrixOp::Multiply(other) => other.then(&matrix),
+    })
+}
+
+/// The rotation by `degrees` about the normalized axis `(x, y, z)`. Quarter
+/// turns are built from their exact sines and cosines rather than going
+/// through `sin` and `cos`, so that `rotate(90)` has an exactly zero `m11` and
+/// four of them give back exactly the identity.
+fn rotation(x: f64, y: f64, z: f64, degrees: f64) -> Transform3D<f64> {
+    if degrees % 90.0 != 0.0 {
+        return Transform3D::rotation(x, y, z, Angle::radians(degrees.to_radians()));
+    }
+    // sin(θ/2)·cos(θ/2) and sin²(θ/2), which euclid's rotation is built from.
+    let (sc, sq) = match (degrees / 90.0).rem_euclid(4.0) as u8 {
+        0 => (0.0, 0.0),
+        1 => (0.5, 0.5),
+        2 => (0.0, 1.0),
+        _ => (-0.5, 0.5),
+    };
+    Transform3D::new(
+        1.0 - 2.0 * (y * y + z * z) * sq,
+        2.0 * (x * y * sq + z * sc),
+        2.0 * (x * z * sq - y * sc),
+        0.0,
+        2.0 * (x * y * sq - z * sc),
+        1.0 - 2.0 * (x * x + z * z) * sq,
+        2.0 * (y * z * sq + x * sc),
+        0.0,
+        2.0 * (x * z * sq + y * sc),
+        2.0 * (y * z * sq - x * sc),
+        1.0 - 2.0 * (x * x + y * y) * sq,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0,
+    )
+}
+
+/// The rotation described by the quaternion `(x, y, z, w)`, laid out the same
+/// way as `rotation()` so that it agrees with `rotateAxisAngle()`. The
+/// quaternion is normalized first; a zero one gives the identity.
+pub fn quaternion_to_matrix(x: f64, y: f64, z: f64, w: f64) -> Transform3D<f64> {
+    let length = (x * x + y * y + z * z + w * w).sqrt();
+    if length == 0.0 {
+        return Transform3D::identity();
+    }
+    let (x, y, z, w) = (x / length, y / length, z / length, w / length);
+    Transform3D::new(
+        1.0 - 2.0 * (y * y + z * z),
+        2.0 * (x * y + z * w),
+        2.0 * (x * z - y * w),
+        0.0,
+        2.0 * (x * y - z * w),
+        1.0 - 2.0 * (x * x + z * z),
+        2.0 * (y * z + x * w),
+        0.0,
+        2.0 * (x * z + y * w),
+        2.0 * (y * z - x * w),
+        1.0 - 2.0 * (x * x + y * y),
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0,
+    )
+}
+
+/// Check the origin of `scale()`, `scale2d()`, `scale3d()` or their `*Self()`
+/// variants. NaN origins are allowed by
+/// the spec and make the whole matrix NaN; with `strict`, they are rejected
+/// with a `TypeError` instead.
+pub fn validate_scale_origin(origin: [f64; 3], strict: bool) -> Fallible<()> {
+    if strict && origin.iter().any(|coordinate| coordinate.is_nan()) {
+        return Err(error::Error::Type(
+            "The scale origin must not be NaN.".to_owned(),
+        ));
+    }
+    Ok(())
+}
+
+/// `matrix · other`, as computed by `multiplySelf()`. Multiplying by the
+/// identity is common in transform stacks with optional steps, so it is
+/// skipped and leaves `matrix` exactly as it was. That is only what the full
+/// multiply gives when `matrix` is finite: otherwise the zeros of the identity
+/// turn its infinities into NaNs, as in `Infinity * 0`.
+pub fn post_multiply(
+    matrix: &Transform3D<f64>,
+    is_2d: bool,
+    other: &Transform3D<f64>,
+    other_is_2d: bool,
+) -> Transform3D<f64> {
+    if *other == Transform3D::identity() && matrix_is_finite(matrix) {
+        *matrix
+    } else if is_2d && other_is_2d {
+        then_2d(other, matrix)
+    } else {
+        other.then(matrix)
+    }
+}
+
+/// `other · matrix`, as computed by `preMultiplySelf()`, skipping the identity
+/// like `post_multiply` does.
+pub fn pre_multiply(
+    matrix: &Transform3D<f64>,
+    is_2d: bool,
+    other: &Transform3D<f64>,
+    other_is_2d: bool,
+) -> Transform3D<f64> {
+    if *other == Transform3D::identity() && matrix_is_finite(matrix) {
+        *matrix
+    } else if is_2d && other_is_2d {
+        then_2d(matrix, other)
+    } else {
+        matrix.then(other)
+    }
+}
+
+/// Post-multiply `matrix` by each of `others` in turn, left to right, so that
+/// the result is `matrix · others[0] · others[1] · …`, as chained calls to
+/// `multiply()` would compute. Returns the is2D flag and matrix of the result.
+pub fn multiply_all(
+    is_2d: bool,
+    matrix: &Transform3D<f64>,
+    others: &[DOMMatrixInit],
+) -> Fallible<(bool, Transform3D<f64>)> {
+    others
+        .iter()
+        .try_fold((is_2d, *matrix), |(is_2d, matrix), other| {
+            let (other_is_2d, other_matrix) = dommatrixinit_to_matrix(other)?;
+            let matrix = post_multiply(&matrix, is_2d, &other_matrix, other_is_2d);
+            Ok((is_2d && other_is_2d, matrix))
+        })
+}
+
+/// Steps 1 and 2 of `invertSelf()`: the inverse of `matrix` and its is2D
+/// flag. A 2D matrix is inverted through its 2D affine components, which is
+/// much cheaper than the full 4x4 inverse. A matrix that cannot be inverted
+/// becomes all NaN and is no longer 2D.
+pub fn invert_matrix(matrix: &Transform3D<f64>, is_2d: bool) -> (bool, Transform3D<f64>) {
+    let inverse = if is_2d {
+        matrix.to_2d().inverse().map(|inverse| inverse.to_3d())
+    } else {
+        matrix.inverse()
+    };
+    match inverse {
+        // Step 1.
+        Some(inverse) => (is_2d, inverse),
+        // Step 2.
+        None => (
+            false,
+            Transform3D::new(
+                f64::NAN,
+                f64::NAN,
+                f64::NAN,
+                f64::NAN,
+                f64::NAN,
+                f64::NAN,
+                f64::NAN,
+                f64::NAN,
+                f64::NAN,
+                f64::NAN,
+                f64::NAN,
+                f64::NAN,
+                f64::NAN,
+                f64::NAN,
+                f64::NAN,
+                f64::NAN,
+            ),
+        ),
+    }
+}
+
+/// `first.then(second)` for two 2D matrices, only computing the six
+/// components that can differ from the identity. The skipped terms are only
+/// zero when both matrices are finite, as `Infinity * 0` is NaN, so other
+/// matrices get the full multiply.
+pub fn then_2d(first: &Transform3D<f64>, second: &Transform3D<f64>) -> Transform3D<f64> {
+    if !matrix_is_finite(first) || !matrix_is_finite(second) {
+        return first.then(second);
+    }
+    first.to_2d().then(&second.to_2d()).to_3d()
+}
+
+/// The 2D components of `matrix`, as returned by `DOMMatrixReadOnly::to_2d_components`.
+pub fn matrix_2d_components(matrix: &Transform3D<f64>, is_2d: bool) -> Fallible<[f64; 6]> {
+    if !is_2d {
+        return Err(error::Error::InvalidState);
+    }
+    Ok([
+        matrix.m11, matrix.m12, matrix.m21, matrix.m22, matrix.m41, matrix.m42,
+    ])
+}
+
+/// Whether a matrix stays 2D once its `m{row}{col}` component is set to
+/// `value`: any value is fine in one of the six 2D components, but the others
+/// have to keep the value of the identity, with -0 counting as 0.
+/// <https://drafts.fxtf.org/geometry-1/#dom-dommatrix-m13>
+pub fn component_keeps_2d(row: usize, col: usize, value: f64) -> bool {
+    match (row, col) {
+        (1 | 2 | 4, 1 | 2) => true,
+        (3, 3) | (4, 4) => value == 1.0,
+        _ => value == 0.0,
+    }
+}
+
+/// Set the `m{row}{col}` component of `matrix`, as done by
+/// `DOMMatrixReadOnly::set_component`.
+pub fn set_matrix_component(
+    matrix: &mut Transform3D<f64>,
+    row: usize,
+    col: usize,
+    value: f64,
+) -> Fallible<()> {
+    let component = match (row, col) {
+        (1, 1) => &mut matrix.m11,
+        (1, 2) => &mut matrix.m12,
+        (1, 3) => &mut matrix.m13,
+        (1, 4) => &mut matrix.m14,
+        (2, 1) => &mut matrix.m21,
+        (2, 2) => &mut matrix.m22,
+        (2, 3) => &mut matrix.m23,
+        (2, 4) => &mut matrix.m24,
+        (3, 1) => &mut matrix.m31,
+        (3, 2) => &mut matrix.m32,
+        (3, 3) => &mut matrix.m33,
+        (3, 4) => &mut matrix.m34,
+        (4, 1) => &mut matrix.m41,
+        (4, 2) => &mut matrix.m42,
+        (4, 3) => &mut matrix.m43,
+        (4, 4) => &mut matrix.m44,
+        _ => return Err(error::Error::IndexSize),
+    };
+    *component = value;
+    Ok(())
+}
+
+/// Whether none of the components of `matrix` is NaN or infinite.
+pub fn matrix_is_finite(matrix: &Transform3D<f64>) -> bool {
+    matrix
+        .to_array()
+        .iter()
+        .all(|component| component.is_finite())
+}
+
+/// See `DOMMatrixReadOnly::ensure_finite`.
+pub fn ensure_matrix_finite(matrix: &Transform3D<f64>) -> Fallible<()> {
+    if matrix_is_finite(matrix) {
+        Ok(())
+    } else {
+        Err(error::Error::InvalidState)
+    }
+}
+
+// https://drafts.fxtf.org/geometry-1/#dommatrixreadonly-stringification-behavior
+pub fn serialize_matrix(matrix: &Transform3D<f64>, is_2d: bool) -> Fallible<String> {
+    // Step 1.
+    ensure_matrix_finite(matrix)?;
+    // Steps 2-3.
+    let (function, components) = if is_2d {
+        (
+            "matrix",
+            vec![
+                matrix.m11, matrix.m12, matrix.m21, matrix.m22, matrix.m41, matrix.m42,
+            ],
+        )
+    } else {
+        ("matrix3d", matrix.to_array().to_vec())
+    };
+    let components: Vec<String> = components.into_iter().map(number_to_string).collect();
+    Ok(format!("{}({})", function, components.join(", ")))
+}
+
+/// ECMAScript's `Number::toString` for finite values.
+/// <https://tc39.es/ecma262/#sec-numeric-types-number-tostring>
+fn number_to_string(value: f64) -> String {
+    if value == 0.0 {
+        return "0".to_owned();
+    }
+    let sign = if value < 0.0 { "-" } else { "" };
+    // Rust's exponential formatting gives the shortest round-tripping digits,
+    // which only have to be laid out the way ECMAScript does.
+    let exponential = format!("{:e}", value.abs());
+    let (mantissa, exponent) = exponential.split_once('e').unwrap();
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let k = digits.len() as i32;
+    let n = exponent.parse::<i32>().unwrap() + 1;
+    let body = if k <= n && n <= 21 {
+        digits + &"0".repeat((n - k) as usize)
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat(-n as usize), digits)
+    } else {
+        let exponent = format!("e{}{}", if n > 0 { '+' } else { '-' }, (n - 1).abs());
+        if k == 1 {
+            digits + &exponent
+        } else {
+            format!("{}.{}{}", &digits[..1], &digits[1..], exponent)
+        }
+    };
+    format!("{}{}", sign, body)
+}
+
+/// Column-major access to the components of `matrix`, matching the order of
+/// `toFloat64Array()`.
+pub fn matrix_column(matrix: &Transform3D<f64>, index: usize) -> Option<[f64; 4]> {
+    if index >= 4 {
+        return None;
+    }
+    let entries = matrix.to_array();
+    let start = index * 4;
+    Some([
+        entries[start],
+        entries[start + 1],
+        entries[start + 2],
+        entries[start + 3],
+    ])
+}
+
+/// Row-major access to the components of `matrix`.
+pub fn matrix_row(matrix: &Transform3D<f64>, index: usize) -> Option<[f64; 4]> {
+    if index >= 4 {
+        return None;
+    }
+    let entries = matrix.to_array();
+    Some([
+        entries[index],
+        entries[index + 4],
+        entries[index + 8],
+        entries[index + 12],
+    ])
+}
+
+/// The translation, scale and rotation parts of a decomposed matrix.
+#[derive(Clone, Copy, Debug)]
+pub struct DecomposedMatrix {
+    pub translate: [f64; 3],
+    pub scale: [f64; 3],
+    /// The rotation as an `[x, y, z, w]` unit quaternion.
+    pub quaternion: [f64; 4],
+}
+
+/// Decompose `matrix` with the same algorithm used when interpolating
+/// transforms, or `None` if it is singular.
+/// <https://drafts.csswg.org/css-transforms-2/#decomposing-a-3d-matrix>
+pub fn decompose_matrix(matrix: &Transform3D<f64>) -> Option<DecomposedMatrix> {
+    let decomposed = decompose_3d_matrix(Matrix3D::from(matrix.cast::<f32>())).ok()?;
+    let (translate, scale, quaternion) = (
+        decomposed.translate,
+        decomposed.scale,
+        decomposed.quaternion,
+    );
+    Some(DecomposedMatrix {
+        translate: [translate.0 as f64, translate.1 as f64, translate.2 as f64],
+        scale: [scale.0 as f64, scale.1 as f64, scale.2 as f64],
+        quaternion: [quaternion.0, quaternion.1, quaternion.2, quaternion.3],
+    })
+}
+
+/// The rotation quaternion of `matrix`, from `cache` if it holds one, or
+/// decomposed and stored in `cache` otherwise. A matrix that cannot be
+/// decomposed is not cached.
+pub fn cached_rotation_quaternion(
+    cache: &Cell<Option<[f64; 4]>>,
+    matrix: &Transform3D<f64>,
+) -> Option<[f64; 4]> {
+    if let Some(quaternion) = cache.get() {
+        return Some(quaternion);
+    }
+    let quaternion = decompose_matrix(matrix)?.quaternion;
+    cache.set(Some(quaternion));
+    Some(quaternion)
+}
+
+/// The is2D flag and components of `matrix` after `translateSelf(tx, ty, tz)`.
+///
+/// <https://drafts.fxtf.org/geometry-1/#dom-dommatrix-translateself>
+pub fn translated_matrix(
+    is_2d: bool,
+    matrix: &Transform3D<f64>,
+    tx: f64,
+    ty: f64,
+    tz: f64,
+) -> (bool, Transform3D<f64>) {
+    // Step 1.
+    let matrix = Transform3D::translation(tx, ty, tz).then(matrix);
+    // Step 2.
+    (is_2d && tz == 0.0, matrix)
+}
+
+/// The is2D flag and components of the copy made by `clone()`. The copy
+/// shares nothing with the original, so mutating it leaves the original as it
+/// was, and every component is kept bit for bit, `-0.0` and NaN included.
+pub fn clone_matrix(is_2d: bool, matrix: &Transform3D<f64>) -> (bool, Transform3D<f64>) {
+    (is_2d, *matrix)
+}
+
+/// Whether `matrix`, with the given is2D flag, is exactly equal to `other`:
+/// the same is2D flag and the same sixteen components under `==`, so `-0.0`
+/// equals `0.0` but a NaN component is never equal to anything.
+pub fn matrix_equals(
+    is_2d: bool,
+    matrix: &Transform3D<f64>,
+    other: &DOMMatrixInit,
+) -> Fallible<bool> {
+    let (other_is_2d, other) = dommatrixinit_to_matrix(other)?;
+    Ok(is_2d == other_is_2d &&
+        matrix
+            .to_array()
+            .iter()
+            .zip(other.to_array().iter())
+            .all(|(a, b)| a == b))
+}
+
+/// Hash the bit patterns of the sixteen components of `matrix`, treating
+/// `-0.0` as `0.0` and all NaNs as the same value so that matrices that
+/// compare equal also hash equally.
+pub fn matrix_hash(matrix: &Transform3D<f64>) -> u64 {
+    let mut hasher = FnvHasher::default();
+    for component in matrix.to_array() {
+        let canonical = if component.is_nan() {
+            f64::NAN
+        } else if component == 0. {
+            0.
+        } else {
+            component
+        };
+        hasher.write_u64(canonical.to_bits());
+    }
+    hasher.finish()
+}
+
+/// The components of `matrix` in row-major order, i.e. the transpose of
+/// the column-major `to_array()`.
+pub fn matrix_to_row_major(matrix: &Transform3D<f64>) -> [f64; 16] {
+    matrix.to_array_transposed()
+}
+
+/// The inverse of `matrix_to_row_major`.
+pub fn matrix_from_row_major(entries: &[f64; 16]) -> Transform3D<f64> {
+    create_3d_matrix(entries).transpose()
+}
+
 // https://drafts.fxtf.org/geometry-1/#create-a-2d-matrix
 fn create_2d_matrix(entries: &[f64]) -> Transform3D<f64> {
     Transform3D::new(
@@ -732,6 +1620,58 @@ fn create_3d_matrix(entries: &[f64]) -> Transform3D<f64> {
     )
 }
 
+/// Read the entries of a matrix stored in `bytes` as consecutive floats of the
+/// given format, starting at `byte_offset` and running to the end of `bytes`.
+/// As with typed array views, the offset and the remaining length must both be
+/// a multiple of the element size.
+pub fn matrix_entries_from_bytes(
+    bytes: &[u8],
+    byte_offset: usize,
+    format: DOMMatrixBufferFormat,
+) -> Fallible<Vec<f64>> {
+    let element_size = match format {
+        DOMMatrixBufferFormat::Float32 => 4,
+        DOMMatrixBufferFormat::Float64 => 8,
+    };
+    if byte_offset % element_size != 0 {
+        let err_msg = format!(
+            "Byte offset {} is not a multiple of {}.",
+            byte_offset, element_size
+        );
+        return Err(error::Error::Range(err_msg));
+    }
+    let bytes = bytes.get(byte_offset..).ok_or_else(|| {
+        let err_msg = format!(
+            "Byte offset {} is past the end of a buffer of {} bytes.",
+            byte_offset,
+            bytes.len()
+        );
+        error::Error::Range(err_msg)
+    })?;
+    if bytes.len() % element_size != 0 {
+        let err_msg = format!(
+            "Buffer length after the byte offset is not a multiple of {}.",
+            element_size
+        );
+        return Err(error::Error::Range(err_msg));
+    }
+    let entries = match format {
+        DOMMatrixBufferFormat::Float32 => bytes
+            .chunks_exact(4)
+            .map(|chunk| f32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]) as f64)
+            .collect(),
+        DOMMatrixBufferFormat::Float64 => bytes
+            .chunks_exact(8)
+            .map(|chunk| {
+                let mut array = [0; 8];
+                array.copy_from_slice(chunk);
+                f64::from_ne_bytes(array)
+            })
+            .collect(),
+    };
+    Ok(entries)
+}
+
 // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-dommatrixreadonly-numbersequence
 pub fn entries_to_matrix(entries: &[f64]) -> Fallible<(bool, Transform3D<f64>)> {
     if entries.len() == 6 {
@@ -744,80 +1684,102 @@ pub fn entries_to_matrix(entries: &[f64]) -> Fallible<(bool, Transform3D<f64>)>
     }
 }
 
+/// The matrix whose rows, in math notation, are `rows`: either the 2 rows
+/// `[a, c, e]` and `[b, d, f]` of a 2D matrix, or the 4 rows of a 3D one,
+/// `[m11, m21, m31, m41]` first. Like the sequence constructor, 4 rows always
+/// make a 3D matrix.
+pub fn matrix_from_rows(rows: &[Vec<f64>]) -> Fallible<(bool, Transform3D<f64>)> {
+    let row_lengths: Vec<usize> = rows.iter().map(Vec::len).collect();
+    match row_lengths[..] {
+        [3, 3] => {
+            let (first, second) = (&rows[0], &rows[1]);
+            Ok((
+                true,
+                Transform3D::new_2d(
+                    first[0], second[0], first[1], second[1], first[2], second[2],
+                ),
+            ))
+        },
+        [4, 4, 4, 4] => {
+            let mut entries = [0.0; 16];
+            for (entry, value) in entries.iter_mut().zip(rows.iter().flatten()) {
+                *entry = *value;
+            }
+            Ok((false, matrix_from_row_major(&entries)))
+        },
+        _ => Err(error::Error::Type(format!(
+            "Expected 2 rows of 3 or 4 rows of 4 entries, but found rows of {:?}.",
+            row_lengths
+        ))),
+    }
+}
+
+// https://drafts.fxtf.org/geometry-1/#validate-and-fixup-2d
+pub fn dommatrix2dinit_to_matrix(dict: &DOMMatrix2DInit) -> Fallible<Transform2D<f64>> {
+    // Step 1.
+    if dict.a.is_some() && dict.m11.is_some() && dict.a.unwrap() != dict.m11.unwrap() ||
+        dict.b.is_some() && dict.m12.is_some() && dict.b.unwrap() != dict.m12.unwrap() ||
+        dict.c.is_some() && dict.m21.is_some() && dict.c.unwrap() != dict.m21.unwrap() ||
+        dict.d.is_some() && dict.m22.is_some() && dict.d.unwrap() != dict.m22.unwrap() ||
+        dict.e.is_some() && dict.m41.is_some() && dict.e.unwrap() != dict.m41.unwrap() ||
+        dict.f.is_some() && dict.m42.is_some() && dict.f.unwrap() != dict.m42.unwrap()
+    {
+        return Err(error::Error::Type("Invalid matrix initializer.".to_owned()));
+    }
+    // Step 2.
+    let m11 = dict.m11.unwrap_or(dict.a.unwrap_or(1.0));
+    // Step 3.
+    let m12 = dict.m12.unwrap_or(dict.b.unwrap_or(0.0));
+    // Step 4.
+    let m21 = dict.m21.unwrap_or(dict.c.unwrap_or(0.0));
+    // Step 5.
+    let m22 = dict.m22.unwrap_or(dict.d.unwrap_or(1.0));
+    // Step 6.
+    let m41 = dict.m41.unwrap_or(dict.e.unwrap_or(0.0));
+    // Step 7.
+    let m42 = dict.m42.unwrap_or(dict.f.unwrap_or(0.0));
+    Ok(Transform2D::new(m11, m12, m21, m22, m41, m42))
+}
+
 // https://drafts.fxtf.org/geometry-1/#validate-and-fixup
 pub fn dommatrixinit_to_matrix(dict: &DOMMatrixInit) -> Fallible<(bool, Transform3D<f64>)> {
     // Step 1.
-    if dict.parent.a.is_some() &&
-        dict.parent.m11.is_some() &&
-        dict.parent.a.unwrap() != dict.parent.m11.unwrap() ||
-        dict.parent.b.is_some() &&
-            dict.parent.m12.is_some() &&
-            dict.parent.b.unwrap() != dict.parent.m12.unwrap() ||
-        dict.parent.c.is_some() &&
-            dict.parent.m21.is_some() &&
-            dict.parent.c.unwrap() != dict.parent.m21.unwrap() ||
-        dict.parent.d.is_some() &&
-            dict.parent.m22.is_some() &&
-            dict.parent.d.unwrap() != dict.parent.m22.unwrap() ||
-        dict.parent.e.is_some() &&
-            dict.parent.m41.is_some() &&
-            dict.parent.e.unwrap() != dict.parent.m41.unwrap() ||
-        dict.parent.f.is_some() &&
-            dict.parent.m42.is_some() &&
-            dict.parent.f.unwrap() != dict.parent.m42.unwrap() ||
-        dict.is2D.is_some() &&
-            dict.is2D.unwrap() &&
-            (dict.m31 != 0.0 ||
-                dict.m32 != 0.0 ||
-                dict.m13 != 0.0 ||
-                dict.m23 != 0.0 ||
-                dict.m43 != 0.0 ||
-                dict.m14 != 0.0 ||
-                dict.m24 != 0.0 ||
-                dict.m34 != 0.0 ||
-                dict.m33 != 1.0 ||
-                dict.m44 != 1.0)
-    {
-        Err(error::Error::Type("Invalid matrix initializer.".to_owned()))
-    } else {
-        let mut is_2d = dict.is2D;
-        // Step 2.
-        let m11 = dict.parent.m11.unwrap_or(dict.parent.a.unwrap_or(1.0));
-        // Step 3.
-        let m12 = dict.parent.m12.unwrap_or(dict.parent.b.unwrap_or(0.0));
-        // Step 4.
-        let m21 = dict.parent.m21.unwrap_or(dict.parent.c.unwrap_or(0.0));
-        // Step 5.
-        let m22 = dict.parent.m22.unwrap_or(dict.parent.d.unwrap_or(1.0));
-        // Step 6.
-        let m41 = dict.parent.m41.unwrap_or(dict.parent.e.unwrap_or(0.0));
-        // Step 7.
-        let m42 = dict.parent.m42.unwrap_or(dict.parent.f.unwrap_or(0.0));
-        // Step 8.
-        if is_2d.is_none() &&
-            (dict.m31 != 0.0 ||
-                dict.m32 != 0.0 ||
-                dict.m13 != 0.0 ||
-                dict.m23 != 0.0 ||
-                dict.m43 != 0.0 ||
-                dict.m14 != 0.0 ||
-                dict.m24 != 0.0 ||
-                dict.m34 != 0.0 ||
-                dict.m33 != 1.0 ||
-                dict.m44 != 1.0)
-        {
-            is_2d = Some(false);
-        }
-        // Step 9.
-        if is_2d.is_none() {
-            is_2d = Some(true);
-        }
-        let matrix = Transform3D::new(
-            m11, m12, dict.m13, dict.m14, m21, m22, dict.m23, dict.m24, dict.m31, dict.m32,
-            dict.m33, dict.m34, m41, m42, dict.m43, dict.m44,
-        );
-        Ok((is_2d.unwrap(), matrix))
-    }
+    let matrix_2d = dommatrix2dinit_to_matrix(&dict.parent)?;
+    let has_3d_components = dict.m31 != 0.0 ||
+        dict.m32 != 0.0 ||
+        dict.m13 != 0.0 ||
+        dict.m23 != 0.0 ||
+        dict.m43 != 0.0 ||
+        dict.m14 != 0.0 ||
+        dict.m24 != 0.0 ||
+        dict.m34 != 0.0 ||
+        dict.m33 != 1.0 ||
+        dict.m44 != 1.0;
+    // Step 2.
+    if dict.is2D == Some(true) && has_3d_components {
+        return Err(error::Error::Type("Invalid matrix initializer.".to_owned()));
+    }
+    // Steps 3-4.
+    let is_2d = dict.is2D.unwrap_or(!has_3d_components);
+    let matrix = Transform3D::new(
+        matrix_2d.m11,
+        matrix_2d.m12,
+        dict.m13,
+        dict.m14,
+        matrix_2d.m21,
+        matrix_2d.m22,
+        dict.m23,
+        dict.m24,
+        dict.m31,
+        dict.m32,
+        dict.m33,
+        dict.m34,
+        matrix_2d.m31,
+        matrix_2d.m32,
+        dict.m43,
+        dict.m44,
+    );
+    Ok((is_2d, matrix))
 }
 
 #[inline]
@@ -830,11 +1792,112 @@ fn normalize_point(x: f64, y: f64, z: f64) -> (f64, f64, f64) {
     }
 }
 
+/// How many transform strings `transform_to_matrix` remembers the parse of.
+const TRANSFORM_CACHE_SIZE: usize = 8;
+
+thread_local!(static TRANSFORM_CACHE: RefCell<TransformCache> =
+    RefCell::new(TransformCache::new(TRANSFORM_CACHE_SIZE)));
+
+/// A small least-recently-used cache of parsed transform strings, so that
+/// constructing matrices from the same string over and over only parses it once.
+pub struct TransformCache {
+    capacity: usize,
+    /// The most recently used entry is at the back.
+    entries: VecDeque<(String, (bool, Transform3D<f64>))>,
+}
+
+impl TransformCache {
+    pub fn new(capacity: usize) -> Self {
+        TransformCache {
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// The cached result for `value`, or the result of `parse` on a miss.
+    /// Only successful parses are cached.
+    pub fn get_or_parse(
+        &mut self,
+        value: &str,
+        parse: impl FnOnce(&str) -> Fallible<(bool, Transform3D<f64>)>,
+    ) -> Fallible<(bool, Transform3D<f64>)> {
+        if let Some(index) = self.entries.iter().position(|(key, _)| key == value) {
+            let entry = self.entries.remove(index).unwrap();
+            let result = entry.1;
+            self.entries.push_back(entry);
+            return Ok(result);
+        }
+        let result = parse(value)?;
+        if self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((value.to_owned(), result));
+        Ok(result)
+    }
+}
+
 pub fn transform_to_matrix(value: String) -> Fallible<(bool, Transform3D<f64>)> {
+    TRANSFORM_CACHE.with(|cache| cache.borrow_mut().get_or_parse(&value, parse_transform))
+}
+
+fn parse_transform(value: &str) -> Fallible<(bool, Transform3D<f64>)> {
     use style::properties::longhands::transform;
 
-    let mut input = ParserInput::new(&value);
-    let mut parser = Parser::new(&mut input);
+    let transform = match with_parser_context(|context| {
+        let mut input = ParserInput::new(value);
+        let mut parser = Parser::new(&mut input);
+        parser
+            .parse_entirely(|t| transform::parse(context, t))
+            .map_err(|_| ())
+    }) {
+        Ok(result) => result,
+        Err(..) => return Err(error::Error::Syntax),
+    };
+
+    let (m, is_3d) = match transform.to_transform_3d_matrix_f64(None) {
+        Ok(result) => result,
+        Err(..) => return Err(error::Error::Syntax),
+    };
+
+    Ok((!is_3d, m))
+}
+
+/// Like `transform_to_matrix`, but applies the transform about
+/// `(origin_x, origin_y)` the way CSS applies `transform-origin`: translate to
+/// the origin, apply the transform, and translate back.
+/// <https://drafts.csswg.org/css-transforms/#transform-rendering>
+pub fn transform_to_matrix_with_origin(
+    value: String,
+    origin_x: f64,
+    origin_y: f64,
+) -> Fallible<(bool, Transform3D<f64>)> {
+    let (is_2d, matrix) = transform_to_matrix(value)?;
+    let matrix = Transform3D::translation(-origin_x, -origin_y, 0.0)
+        .then(&matrix)
+        .then(&Transform3D::translation(origin_x, origin_y, 0.0));
+    Ok((is_2d, matrix))
+}
+
+/// Parse a CSS `<angle>`, such as `"0.25turn"`, into degrees.
+pub fn parse_angle_in_degrees(value: &str) -> Option<f64> {
+    with_parser_context(|context| {
+        let mut input = ParserInput::new(value);
+        let mut parser = Parser::new(&mut input);
+        parser
+            .parse_entirely(|input| specified::Angle::parse(context, input))
+            .ok()
+            .map(|angle| angle.degrees() as f64)
+    })
+}
+
+/// The angle in degrees of a CSS `<angle>` argument, or a SyntaxError if
+/// `value` is not one. Plain numbers are not `<angle>`s: they go through the
+/// standard overloads, which convert them with ToNumber.
+pub fn angle_argument_in_degrees(value: &str) -> Fallible<f64> {
+    parse_angle_in_degrees(value).ok_or(error::Error::Syntax)
+}
+
+fn with_parser_context<R>(f: impl FnOnce(&ParserContext) -> R) -> R {
     let url = ::servo_url::ServoUrl::parse("about:blank").unwrap();
     let context = ParserContext::new(
         ::style::stylesheets::Origin::Author,
@@ -846,16 +1909,5 @@ pub fn transform_to_matrix(value: String) -> Fallible<(bool, Transform3D<f64>)>
         None,
         None,
     );
-
-    let transform = match parser.parse_entirely(|t| transform::parse(&context, t)) {
-        Ok(result) => result,
-        Err(..) => return Err(error::Error::Syntax),
-    };
-
-    let (m, is_3d) = match transform.to_transform_3d_matrix_f64(None) {
-        Ok(result) => result,
-        Err(..) => return Err(error::Error::Syntax),
-    };
-
-    Ok((!is_3d, m))
+    f(&context)
 }
@@ -54,6 +54,7 @@ use script_traits::{
     BroadcastMsg, MessagePortMsg, MsDuration, PortMessageTask, ScriptMsg,
     ScriptToConstellationChan, TimerEvent, TimerEventId, TimerSchedulerMsg, TimerSource,
 };
+use servo_config::pref;
 use servo_url::{ImmutableOrigin, MutableOrigin, ServoUrl};
 use uuid::Uuid;
 use webgpu::identity::WebGPUOpResult;
@@ -100,7 +101,7 @@ use crate::dom::messageevent::MessageEvent;
 use crate::dom::messageport::MessagePort;
 use crate::dom::paintworkletglobalscope::PaintWorkletGlobalScope;
 use crate::dom::performance::Performance;
-use crate::dom::performanceobserver::VALID_ENTRY_TYPES;
+use crate::dom::performanceobserver::{entry_type_is_enabled, supported_entry_types};
 use crate::dom::promise::Promise;
 use crate::dom::readablestream::{ExternalUnderlyingSource, ReadableStream};
 use crate::dom::serviceworker::ServiceWorker;
@@ -3009,7 +3010,9 @@ impl GlobalScope {
             return types.get();
         }
 
-        let types: Vec<DOMString> = VALID_ENTRY_TYPES
+        // The list is frozen on first use, so a pref flipped afterwards only
+        // shows up in new globals.
+        let types: Vec<DOMString> = supported_entry_types(entry_type_is_enabled)
             .iter()
             .map(|t| DOMString::from(t.to_string()))
             .collect();
@@ -3055,6 +3058,12 @@ impl GlobalScope {
         false
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#concept-settings-object-cross-origin-isolated-capability>
+    pub fn is_cross_origin_isolated(&self) -> bool {
+        // TODO: derive this from the agent cluster once COOP and COEP are implemented.
+        pref!(dom.testing.cross_origin_isolated)
+    }
+
     /// https://www.w3.org/TR/CSP/#get-csp-of-object
     pub fn get_csp_list(&self) -> Option<CspList> {
         if let Some(window) = self.downcast::<Window>() {
@@ -14,11 +14,14 @@ use ipc_channel::ipc::IpcSharedMemory;
 use js::jsapi::{DetachArrayBuffer, Heap, JSObject, NewExternalArrayBuffer};
 use webgpu::identity::WebGPUOpResult;
 use webgpu::wgpu::device::HostMap;
-use webgpu::{WebGPU, WebGPUBuffer, WebGPURequest, WebGPUResponse, WebGPUResponseResult};
+use webgpu::{
+    ErrorScopeId, WebGPU, WebGPUBuffer, WebGPUDevice, WebGPURequest, WebGPUResponse,
+    WebGPUResponseResult,
+};
 
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::WebGPUBinding::{
-    GPUBufferMethods, GPUMapModeConstants, GPUSize64,
+    GPUBufferMethods, GPUBufferUsageFlags, GPUMapModeConstants, GPUSize64,
 };
 use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
@@ -35,7 +38,7 @@ const RANGE_OFFSET_ALIGN_MASK: u64 = 8;
 const RANGE_SIZE_ALIGN_MASK: u64 = 4;
 
 // https://gpuweb.github.io/gpuweb/#buffer-state
-#[derive(Clone, Copy, MallocSizeOf, PartialEq)]
+#[derive(Clone, Copy, Debug, MallocSizeOf, PartialEq)]
 pub enum GPUBufferState {
     Mapped,
     MappedAtCreation,
@@ -55,6 +58,18 @@ pub struct GPUBufferMapInfo {
     pub map_mode: Option<u32>,
 }
 
+/// What `GPUBuffer::debug_snapshot` reports about a buffer.
+#[derive(Clone, Debug)]
+pub struct GPUBufferDebugInfo {
+    pub id: WebGPUBuffer,
+    pub size: GPUSize64,
+    pub usage: GPUBufferUsageFlags,
+    pub state: GPUBufferState,
+    pub label: String,
+    /// The ranges handed out by `getMappedRange()` since the buffer was mapped.
+    pub mapped_ranges: Vec<Range<u64>>,
+}
+
 #[dom_struct]
 pub struct GPUBuffer {
     reflector_: Reflector,
@@ -66,7 +81,11 @@ pub struct GPUBuffer {
     #[no_trace]
     buffer: WebGPUBuffer,
     device: Dom<GPUDevice>,
+    /// The id of `device`, for finalization, when `device` may already be gone.
+    #[no_trace]
+    device_id: WebGPUDevice,
     size: GPUSize64,
+    usage: GPUBufferUsageFlags,
     #[ignore_malloc_size_of = "promises are hard"]
     map_promise: DomRefCell<Option<Rc<Promise>>>,
     map_info: DomRefCell<Option<GPUBufferMapInfo>>,
@@ -79,6 +98,7 @@ impl GPUBuffer {
         device: &GPUDevice,
         state: GPUBufferState,
         size: GPUSize64,
+        usage: GPUBufferUsageFlags,
         map_info: DomRefCell<Option<GPUBufferMapInfo>>,
         label: USVString,
     ) -> Self {
@@ -88,9 +108,11 @@ impl GPUBuffer {
             label: DomRefCell::new(label),
             state: Cell::new(state),
             device: Dom::from_ref(device),
+            device_id: device.id(),
             buffer,
             map_promise: DomRefCell::new(None),
             size,
+            usage,
             map_info,
         }
     }
@@ -103,12 +125,13 @@ impl GPUBuffer {
         device: &GPUDevice,
         state: GPUBufferState,
         size: GPUSize64,
+        usage: GPUBufferUsageFlags,
         map_info: DomRefCell<Option<GPUBufferMapInfo>>,
         label: USVString,
     ) -> DomRoot<Self> {
         reflect_dom_object(
             Box::new(GPUBuffer::new_inherited(
-                channel, buffer, device, state, size, map_info, label,
+                channel, buffer, device, state, size, usage, map_info, label,
             )),
             global,
         )
@@ -123,13 +146,88 @@ impl GPUBuffer {
     pub fn state(&self) -> GPUBufferState {
         self.state.get()
     }
+
+    /// A read-only snapshot of the buffer for tooling such as a WebGPU
+    /// inspector. Not exposed to content.
+    // No caller yet: there is no WebGPU inspector in devtools to report to.
+    #[allow(dead_code)]
+    pub fn debug_snapshot(&self) -> GPUBufferDebugInfo {
+        GPUBufferDebugInfo {
+            id: self.buffer,
+            size: self.size,
+            usage: self.usage,
+            state: self.state.get(),
+            label: self.label.borrow().to_string(),
+            mapped_ranges: debug_mapped_ranges(self.map_info.borrow().as_ref()),
+        }
+    }
+
+    /// Move to `state`, checking in debug builds that the transition is one
+    /// the buffer state machine allows.
+    fn set_state(&self, state: GPUBufferState) {
+        debug_assert_buffer_state_transition(self.state.get(), state);
+        self.state.set(state);
+    }
+
+    fn send_destroy(&self, scope_id: Option<ErrorScopeId>) {
+        if let Err(e) = self.channel.0.send((
+            scope_id,
+            WebGPURequest::DestroyBuffer {
+                device_id: self.device_id.0,
+                buffer_id: self.buffer.0,
+            },
+        )) {
+            warn!(
+                "Failed to send WebGPURequest::DestroyBuffer({:?}) ({})",
+                self.buffer.0, e
+            );
+        };
+    }
+
+    fn send_map_complete(&self) {
+        if let Err(e) = self
+            .channel
+            .0
+            .send((None, WebGPURequest::BufferMapComplete(self.buffer.0)))
+        {
+            warn!(
+                "Failed to send BufferMapComplete({:?}) ({})",
+                self.buffer.0, e
+            );
+        }
+    }
+
+    /// Send `offset..offset + size` of the mapping to the buffer while keeping
+    /// it mapped, for buffers that stay mapped for a long time. Not exposed to
+    /// script: WebGPU itself only writes a mapping back on `unmap()`.
+    // No caller yet: nothing in the tree keeps a buffer mapped for long.
+    #[allow(dead_code)]
+    pub fn flush_range(&self, offset: u64, size: u64) -> Fallible<()> {
+        let (offset, bytes) =
+            flush_range_payload(&self.state, self.map_info.borrow().as_ref(), offset, size)?;
+        if let Err(e) = self.channel.0.send((
+            self.device.use_current_scope(),
+            WebGPURequest::FlushMappedRange {
+                buffer_id: self.id().0,
+                array_buffer: IpcSharedMemory::from_bytes(&bytes),
+                offset,
+            },
+        )) {
+            warn!("Failed to send Buffer flush ({:?}) ({})", self.buffer.0, e);
+        }
+        Ok(())
+    }
 }
 
 impl Drop for GPUBuffer {
     fn drop(&mut self) {
-        if let Err(e) = self.Destroy() {
-            error!("GPUBuffer destruction failed with {e:?}!"); // TODO: should we allow panic here?
-        };
+        // The device may have been finalized before the buffer, so neither
+        // `unmap()` nor `destroy()` can be used here: destroy the buffer with
+        // the id kept for the purpose, outside of any error scope. Destroying
+        // a mapped buffer unmaps it on the WebGPU thread.
+        if finalize_mapping(self.state.get(), self.map_info.borrow_mut().as_mut()) {
+            self.send_destroy(None);
+        }
     }
 }
 
@@ -149,17 +247,24 @@ impl GPUBufferMethods for GPUBuffer {
                 let mut info = self.map_info.borrow_mut();
                 let m_info = info.as_mut().unwrap();
                 let m_range = m_info.mapping_range.clone();
+                let is_map_read = is_map_read(m_info.map_mode);
+                let write_ranges = write_back_ranges(&m_info.mapped_ranges);
+                let array_buffer = IpcSharedMemory::from_bytes(unmap_payload(
+                    m_info.mapping.borrow().as_slice(),
+                    m_range.start,
+                    is_map_read,
+                    &write_ranges,
+                ));
                 if let Err(e) = self.channel.0.send((
                     self.device.use_current_scope(),
                     WebGPURequest::UnmapBuffer {
                         buffer_id: self.id().0,
                         device_id: self.device.id().0,
-                        array_buffer: IpcSharedMemory::from_bytes(
-                            m_info.mapping.borrow().as_slice(),
-                        ),
-                        is_map_read: m_info.map_mode == Some(GPUMapModeConstants::READ),
+                        array_buffer,
+                        is_map_read,
                         offset: m_range.start,
                         size: m_range.end - m_range.start,
+                        write_ranges,
                     },
                 )) {
                     warn!("Failed to send Buffer unmap ({:?}) ({})", self.buffer.0, e);
@@ -176,32 +281,20 @@ impl GPUBufferMethods for GPUBuffer {
             },
         };
         // Step 4
-        self.state.set(GPUBufferState::Unmapped);
+        self.set_state(GPUBufferState::Unmapped);
         *self.map_info.borrow_mut() = None;
         Ok(())
     }
 
     /// https://gpuweb.github.io/gpuweb/#dom-gpubuffer-destroy
     fn Destroy(&self) -> Fallible<()> {
-        let state = self.state.get();
-        match state {
-            GPUBufferState::Mapped | GPUBufferState::MappedAtCreation => {
-                self.Unmap()?;
-            },
-            GPUBufferState::Destroyed => return Ok(()),
-            _ => {},
-        };
-        if let Err(e) = self
-            .channel
-            .0
-            .send((None, WebGPURequest::DestroyBuffer(self.buffer.0)))
-        {
-            warn!(
-                "Failed to send WebGPURequest::DestroyBuffer({:?}) ({})",
-                self.buffer.0, e
-            );
+        match destroy_step(self.state.get()) {
+            DestroyStep::UnmapThenDestroy => self.Unmap()?,
+            DestroyStep::Destroy => {},
+            DestroyStep::Nothing => return Ok(()),
         };
-        self.state.set(GPUBufferState::Destroyed);
+        self.send_destroy(self.device.use_current_scope());
+        self.set_state(GPUBufferState::Destroyed);
         Ok(())
     }
 
@@ -215,21 +308,22 @@ impl GPUBufferMethods for GPUBuffer {
         comp: InRealm,
     ) -> Rc<Promise> {
         let promise = Promise::new_in_current_realm(comp);
-        let range_size = if let Some(s) = size {
-            s
-        } else if offset >= self.size {
-            promise.reject_error(Error::Operation);
-            return promise;
-        } else {
-            self.size - offset
-        };
+        let range_size = map_range_size(self.size, offset, size);
         let scope_id = self.device.use_current_scope();
-        if self.state.get() != GPUBufferState::Unmapped {
+        if let Some((message, error)) = map_async_state_error(self.state.get()) {
             self.device.handle_server_msg(
                 scope_id,
-                WebGPUOpResult::ValidationError(String::from("Buffer is not Unmapped")),
+                WebGPUOpResult::ValidationError(String::from(message)),
             );
-            promise.reject_error(Error::Abort);
+            promise.reject_error(error);
+            return promise;
+        }
+        if !is_valid_map_range(self.size, offset, range_size) {
+            self.device.handle_server_msg(
+                scope_id,
+                WebGPUOpResult::ValidationError(String::from("Invalid map range")),
+            );
+            promise.reject_error(Error::Operation);
             return promise;
         }
         let host_map = match mode {
@@ -266,7 +360,7 @@ impl GPUBufferMethods for GPUBuffer {
             return promise;
         }
 
-        self.state.set(GPUBufferState::MappingPending);
+        self.set_state(GPUBufferState::MappingPending);
         *self.map_info.borrow_mut() = Some(GPUBufferMapInfo {
             mapping: Rc::new(RefCell::new(Vec::with_capacity(0))),
             mapping_range: map_range,
@@ -286,48 +380,43 @@ impl GPUBufferMethods for GPUBuffer {
         offset: GPUSize64,
         size: Option<GPUSize64>,
     ) -> Fallible<NonNull<JSObject>> {
-        let range_size = if let Some(s) = size {
-            s
-        } else if offset >= self.size {
-            return Err(Error::Operation);
-        } else {
-            self.size - offset
-        };
-        let m_end = offset + range_size;
+        // Script controls both `offset` and `size`, so the range is computed
+        // without overflowing before anything is checked against it.
+        let requested = requested_mapped_range(self.size, offset, size).ok_or(Error::Operation)?;
+        let range_size = requested.end - requested.start;
         let mut info = self.map_info.borrow_mut();
-        let m_info = info.as_mut().unwrap();
+        let m_info = mapped_buffer_info(self.state.get(), info.as_mut())?;
 
-        let mut valid = match self.state.get() {
-            GPUBufferState::Mapped | GPUBufferState::MappedAtCreation => true,
-            _ => false,
-        };
-        valid &= offset % RANGE_OFFSET_ALIGN_MASK == 0 &&
-            range_size % RANGE_SIZE_ALIGN_MASK == 0 &&
-            offset >= m_info.mapping_range.start &&
-            m_end <= m_info.mapping_range.end;
-        valid &= m_info
-            .mapped_ranges
-            .iter()
-            .all(|range| range.start >= m_end || range.end <= offset);
-        if !valid {
+        if let Err(error) = validate_mapped_range(
+            &m_info.mapping_range,
+            &m_info.mapped_ranges,
+            offset,
+            range_size,
+        ) {
+            warn!(
+                "getMappedRange({}, {}) on buffer {:?}: {}",
+                offset,
+                range_size,
+                self.buffer.0,
+                error.message()
+            );
             return Err(Error::Operation);
         }
-
-        unsafe extern "C" fn free_func(_contents: *mut c_void, free_user_data: *mut c_void) {
-            let _ = Rc::from_raw(free_user_data as _);
-        }
+        // `mapping` only holds the bytes of `mapping_range`.
+        let mapping_start = (offset - m_info.mapping_range.start) as usize;
+        let mapping_end = mapping_start + range_size as usize;
 
         let array_buffer = unsafe {
             NewExternalArrayBuffer(
                 *cx,
                 range_size as usize,
-                m_info.mapping.borrow_mut()[offset as usize..m_end as usize].as_mut_ptr() as _,
-                Some(free_func),
-                Rc::into_raw(m_info.mapping.clone()) as _,
+                m_info.mapping.borrow_mut()[mapping_start..mapping_end].as_mut_ptr() as _,
+                Some(release_mapping_view),
+                retain_mapping_for_view(&m_info.mapping),
             )
         };
 
-        m_info.mapped_ranges.push(offset..m_end);
+        m_info.mapped_ranges.push(requested);
         m_info.js_buffers.push(Heap::boxed(array_buffer));
 
         Ok(NonNull::new(array_buffer).unwrap())
@@ -347,6 +436,20 @@ impl GPUBufferMethods for GPUBuffer {
 impl AsyncWGPUListener for GPUBuffer {
     #[allow(unsafe_code)]
     fn handle_response(&self, response: Option<WebGPUResponseResult>, promise: &Rc<Promise>) {
+        // `unmap()` and `destroy()` reject a pending mapping without waiting for
+        // its response, after which another mapping may have been requested.
+        // The response for the cancelled mapping must leave that one alone.
+        let is_pending = self
+            .map_promise
+            .borrow()
+            .as_ref()
+            .map_or(false, |pending| Rc::ptr_eq(pending, promise));
+        if !is_pending {
+            if self.map_promise.borrow().is_none() {
+                self.send_map_complete();
+            }
+            return;
+        }
         match response {
             Some(response) => match response {
                 Ok(WebGPUResponse::BufferMapAsync(bytes)) => {
@@ -358,26 +461,329 @@ impl AsyncWGPUListener for GPUBuffer {
                         .mapping
                         .borrow_mut() = bytes.to_vec();
                     promise.resolve_native(&());
-                    self.state.set(GPUBufferState::Mapped);
+                    self.set_state(state_after_map_response(true));
                 },
                 Err(e) => {
                     warn!("Could not map buffer({:?})", e);
                     promise.reject_error(Error::Abort);
+                    // Leave nothing pending behind, for `unmap()` and
+                    // `destroy()` to cancel, and for `mapAsync()` to try again.
+                    self.set_state(state_after_map_response(false));
+                    *self.map_info.borrow_mut() = None;
                 },
                 Ok(_) => unreachable!("GPUBuffer received wrong WebGPUResponse"),
             },
             None => unreachable!("Failed to get a response for BufferMapAsync"),
         }
         *self.map_promise.borrow_mut() = None;
-        if let Err(e) = self
-            .channel
-            .0
-            .send((None, WebGPURequest::BufferMapComplete(self.buffer.0)))
-        {
-            warn!(
-                "Failed to send BufferMapComplete({:?}) ({})",
-                self.buffer.0, e
-            );
+        self.send_map_complete();
+    }
+}
+
+/// Whether a buffer may go from state `from` to state `to`: mapping goes
+/// through `MappingPending` on its way to `Mapped`, any kind of mapping can be
+/// unmapped, and anything but a destroyed buffer can be destroyed.
+pub fn is_valid_buffer_state_transition(from: GPUBufferState, to: GPUBufferState) -> bool {
+    use GPUBufferState::*;
+    match (from, to) {
+        (Unmapped, MappingPending) |
+        (MappingPending, Mapped) |
+        (Mapped | MappedAtCreation | MappingPending, Unmapped) => true,
+        (Destroyed, Destroyed) => false,
+        (_, Destroyed) => true,
+        _ => false,
+    }
+}
+
+/// The state a buffer with a pending mapping moves to once the map response
+/// arrives: mapped if it `succeeded`, or back to unmapped, so that it can be
+/// mapped again, if it failed.
+pub fn state_after_map_response(succeeded: bool) -> GPUBufferState {
+    if succeeded {
+        GPUBufferState::Mapped
+    } else {
+        GPUBufferState::Unmapped
+    }
+}
+
+/// The mapped ranges `debug_snapshot()` reports for a buffer with `map_info`:
+/// the ranges handed out by `getMappedRange()` since it was mapped, or none
+/// once it is unmapped.
+pub fn debug_mapped_ranges(map_info: Option<&GPUBufferMapInfo>) -> Vec<Range<u64>> {
+    map_info.map_or_else(Vec::new, |info| info.mapped_ranges.clone())
+}
+
+/// The map info of a buffer in `state`, or an `OperationError` unless it is
+/// mapped. A buffer mapped at creation has map info from the start, just like
+/// one mapped with `mapAsync()`.
+pub fn mapped_buffer_info<T>(state: GPUBufferState, info: Option<T>) -> Fallible<T> {
+    match (state, info) {
+        (GPUBufferState::Mapped | GPUBufferState::MappedAtCreation, Some(info)) => Ok(info),
+        _ => Err(Error::Operation),
+    }
+}
+
+/// Whether a mapping made with `map_mode` is only read by script, so nothing
+/// is written back on unmap. A buffer mapped at creation has no map mode and
+/// is written back.
+pub fn is_map_read(map_mode: Option<u32>) -> bool {
+    map_mode == Some(GPUMapModeConstants::READ)
+}
+
+/// Panic in debug builds if `from` to `to` is not a legal buffer state
+/// transition.
+pub fn debug_assert_buffer_state_transition(from: GPUBufferState, to: GPUBufferState) {
+    debug_assert!(
+        is_valid_buffer_state_transition(from, to),
+        "Illegal GPUBuffer state transition from {:?} to {:?}",
+        from,
+        to
+    );
+}
+
+/// What `destroy()` has to do to a buffer in a given state.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DestroyStep {
+    /// The buffer is already destroyed. Destroying it again is benign and
+    /// doesn't reach the device, so the current error scope captures nothing.
+    Nothing,
+    /// Destroy the buffer on the device, reporting to the current error scope.
+    Destroy,
+    /// Unmap the buffer first, which also rejects a pending mapping.
+    UnmapThenDestroy,
+}
+
+/// What `destroy()` does to a buffer in `state`.
+pub fn destroy_step(state: GPUBufferState) -> DestroyStep {
+    match state {
+        GPUBufferState::Mapped |
+        GPUBufferState::MappedAtCreation |
+        GPUBufferState::MappingPending => DestroyStep::UnmapThenDestroy,
+        GPUBufferState::Unmapped => DestroyStep::Destroy,
+        GPUBufferState::Destroyed => DestroyStep::Nothing,
+    }
+}
+
+/// What finalizing a buffer in `state` does to its mapping, returning whether
+/// the buffer still has to be destroyed, which it doesn't if `destroy()`
+/// already was.
+///
+/// The JS engine can't be called into during finalization, so the views
+/// handed out by `getMappedRange()` can't be detached: they are forgotten
+/// instead. Each holds its own reference to the mapping, so any that script
+/// still uses stay valid, they are just not written back anymore.
+pub fn finalize_mapping(state: GPUBufferState, map_info: Option<&mut GPUBufferMapInfo>) -> bool {
+    if let Some(info) = map_info {
+        info.js_buffers.clear();
+    }
+    destroy_step(state) != DestroyStep::Nothing
+}
+
+/// The validation message and the error to reject `mapAsync()` with when
+/// the buffer is in `state`, or `None` if it can be mapped. A destroyed
+/// buffer gets its own message, as it can never be mapped again.
+pub fn map_async_state_error(state: GPUBufferState) -> Option<(&'static str, Error)> {
+    match state {
+        GPUBufferState::Unmapped => None,
+        GPUBufferState::Destroyed => Some(("Buffer is destroyed", Error::Operation)),
+        _ => Some(("Buffer is not Unmapped", Error::Abort)),
+    }
+}
+
+/// The size of the range `mapAsync()` maps when called with `offset` and
+/// `size` on a buffer of `buffer_size` bytes. A missing `size` extends the
+/// range to the end of the buffer, which is empty if `offset` is at or past it.
+/// <https://gpuweb.github.io/gpuweb/#dom-gpubuffer-mapasync>
+pub fn map_range_size(buffer_size: u64, offset: u64, size: Option<u64>) -> u64 {
+    size.unwrap_or_else(|| buffer_size.saturating_sub(offset))
+}
+
+/// Whether `mapAsync()` can map `offset..offset + size` of a buffer of
+/// `buffer_size` bytes.
+pub fn is_valid_map_range(buffer_size: u64, offset: u64, size: u64) -> bool {
+    offset % RANGE_OFFSET_ALIGN_MASK == 0 &&
+        size % RANGE_SIZE_ALIGN_MASK == 0 &&
+        offset
+            .checked_add(size)
+            .map_or(false, |end| end <= buffer_size)
+}
+
+/// The validation error for creating a buffer of `size` bytes on a device
+/// whose `maxBufferSize` limit is `max_buffer_size`.
+pub fn validate_buffer_size(size: u64, max_buffer_size: u64) -> Result<(), String> {
+    if size > max_buffer_size {
+        return Err(format!(
+            "Buffer size {} exceeds the maxBufferSize limit of {}",
+            size, max_buffer_size
+        ));
+    }
+    Ok(())
+}
+
+/// The error for creating a buffer of `size` bytes with `mappedAtCreation`,
+/// whose size has to be a multiple of 4.
+pub fn validate_mapped_at_creation_size(mapped_at_creation: bool, size: u64) -> Result<(), String> {
+    if mapped_at_creation && size % 4 != 0 {
+        return Err(
+            "Buffers mapped at creation must have a size that is a multiple of 4".to_owned(),
+        );
+    }
+    Ok(())
+}
+
+/// Why `getMappedRange()` refused to hand out a range.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MappedRangeError {
+    /// The offset or size is not aligned.
+    Misaligned,
+    /// The range starts before the mapped region.
+    BeforeMapping,
+    /// The range ends after the mapped region.
+    AfterMapping,
+    /// The range overlaps a range handed out before.
+    Overlapping,
+}
+
+impl MappedRangeError {
+    pub fn message(&self) -> &'static str {
+        match *self {
+            MappedRangeError::Misaligned => "offset must be a multiple of 8 and size of 4",
+            MappedRangeError::BeforeMapping => "range starts before the mapped region",
+            MappedRangeError::AfterMapping => "range ends after the mapped region",
+            MappedRangeError::Overlapping => "range overlaps a previously returned range",
+        }
+    }
+}
+
+/// Take a strong reference to `mapping` on behalf of an ArrayBuffer created by
+/// `getMappedRange()`, to be released by `release_mapping_view`. Every view
+/// holds its own reference, so the mapping outlives all of them in whatever
+/// order they are detached or collected.
+pub fn retain_mapping_for_view(mapping: &Rc<RefCell<Vec<u8>>>) -> *mut c_void {
+    Rc::into_raw(mapping.clone()) as *mut c_void
+}
+
+/// The free function of the ArrayBuffers created by `getMappedRange()`. The
+/// engine calls it exactly once per buffer, when it is detached on unmap or,
+/// failing that, when it is finalized.
+#[allow(unsafe_code)]
+pub unsafe extern "C" fn release_mapping_view(_contents: *mut c_void, free_user_data: *mut c_void) {
+    drop(Rc::from_raw(free_user_data as *const RefCell<Vec<u8>>));
+}
+
+/// The ranges to copy back to the buffer on unmap: the union of the ranges
+/// handed out by `getMappedRange()`, sorted and with adjacent ranges merged.
+/// Bytes in between were never exposed to script and must be left alone.
+pub fn write_back_ranges(mapped_ranges: &[Range<u64>]) -> Vec<Range<u64>> {
+    let mut ranges = mapped_ranges.to_vec();
+    ranges.sort_by_key(|range| range.start);
+    let mut merged: Vec<Range<u64>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if last.end >= range.start => last.end = last.end.max(range.end),
+            _ => merged.push(range),
         }
     }
+    merged
+}
+
+/// The bytes of `mapping`, which starts at `mapping_offset` in the buffer, to
+/// send to the WebGPU thread on unmap. Only the bytes up to the end of the
+/// last of the `write_ranges` are ever copied back, and nothing is for a read
+/// mapping, so the rest is left out of the shared memory allocated for every
+/// unmap rather than copied for nothing.
+pub fn unmap_payload<'a>(
+    mapping: &'a [u8],
+    mapping_offset: u64,
+    is_map_read: bool,
+    write_ranges: &[Range<u64>],
+) -> &'a [u8] {
+    if is_map_read {
+        return &[];
+    }
+    match write_ranges.last() {
+        Some(last) => &mapping[..(last.end - mapping_offset) as usize],
+        None => &[],
+    }
+}
+
+/// The bytes of `mapping`, which holds `mapping_range` of the buffer, that
+/// `flush_range()` sends for `offset..offset + size`, or `None` if that range
+/// is not within the mapping.
+pub fn flush_range_bytes<'a>(
+    mapping: &'a [u8],
+    mapping_range: &Range<u64>,
+    offset: u64,
+    size: u64,
+) -> Option<&'a [u8]> {
+    let end = offset.checked_add(size)?;
+    if offset < mapping_range.start || end > mapping_range.end {
+        return None;
+    }
+    let start = (offset - mapping_range.start) as usize;
+    mapping.get(start..start + size as usize)
+}
+
+/// The buffer offset and the bytes `flush_range()` sends for
+/// `offset..offset + size` of a buffer in `state` mapped with `info`. Only a
+/// writable mapping can be flushed, and flushing leaves `state` alone: the
+/// buffer stays mapped.
+pub fn flush_range_payload(
+    state: &Cell<GPUBufferState>,
+    info: Option<&GPUBufferMapInfo>,
+    offset: u64,
+    size: u64,
+) -> Fallible<(u64, Vec<u8>)> {
+    let m_info = mapped_buffer_info(state.get(), info)?;
+    if is_map_read(m_info.map_mode) {
+        return Err(Error::Operation);
+    }
+    let mapping = m_info.mapping.borrow();
+    let bytes =
+        flush_range_bytes(&mapping, &m_info.mapping_range, offset, size).ok_or(Error::Operation)?;
+    Ok((offset, bytes.to_vec()))
+}
+
+/// The range `getMappedRange()` is asked for with `offset` and `size` on a
+/// buffer of `buffer_size` bytes, or `None` if there is none. A missing
+/// `size` extends the range to the end of the buffer, so `offset` has to be
+/// before it, and an explicit one must not take the range past `u64::MAX`.
+pub fn requested_mapped_range(
+    buffer_size: u64,
+    offset: u64,
+    size: Option<u64>,
+) -> Option<Range<u64>> {
+    let size = match size {
+        Some(size) => size,
+        None if offset < buffer_size => buffer_size - offset,
+        None => return None,
+    };
+    Some(offset..offset.checked_add(size)?)
+}
+
+/// Check that `getMappedRange()` can hand out `offset..offset + size`: it has
+/// to be aligned, within the mapped range, and not overlap a range handed out before.
+pub fn validate_mapped_range(
+    mapping_range: &Range<u64>,
+    mapped_ranges: &[Range<u64>],
+    offset: u64,
+    size: u64,
+) -> Result<(), MappedRangeError> {
+    if offset % RANGE_OFFSET_ALIGN_MASK != 0 || size % RANGE_SIZE_ALIGN_MASK != 0 {
+        return Err(MappedRangeError::Misaligned);
+    }
+    if offset < mapping_range.start {
+        return Err(MappedRangeError::BeforeMapping);
+    }
+    let end = match offset.checked_add(size) {
+        Some(end) if end <= mapping_range.end => end,
+        _ => return Err(MappedRangeError::AfterMapping),
+    };
+    if mapped_ranges
+        .iter()
+        .any(|range| range.start < end && range.end > offset)
+    {
+        return Err(MappedRangeError::Overlapping);
+    }
+    Ok(())
 }
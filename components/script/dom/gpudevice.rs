@@ -51,7 +51,10 @@ use crate::dom::globalscope::GlobalScope;
 use crate::dom::gpuadapter::GPUAdapter;
 use crate::dom::gpubindgroup::GPUBindGroup;
 use crate::dom::gpubindgrouplayout::GPUBindGroupLayout;
-use crate::dom::gpubuffer::{GPUBuffer, GPUBufferMapInfo, GPUBufferState};
+use crate::dom::gpubuffer::{
+    validate_buffer_size, validate_mapped_at_creation_size, GPUBuffer, GPUBufferMapInfo,
+    GPUBufferState,
+};
 use crate::dom::gpucommandencoder::GPUCommandEncoder;
 use crate::dom::gpucomputepipeline::GPUComputePipeline;
 use crate::dom::gpuoutofmemoryerror::GPUOutOfMemoryError;
@@ -362,8 +365,17 @@ impl GPUDeviceMethods for GPUDevice {
 
     /// https://gpuweb.github.io/gpuweb/#dom-gpudevice-createbuffer
     fn CreateBuffer(&self, descriptor: &GPUBufferDescriptor) -> Fallible<DomRoot<GPUBuffer>> {
-        let desc =
-            wgt::BufferUsages::from_bits(descriptor.usage).map(|usg| wgpu_res::BufferDescriptor {
+        validate_mapped_at_creation_size(descriptor.mappedAtCreation, descriptor.size)
+            .map_err(Error::Range)?;
+        let size_error = validate_buffer_size(descriptor.size, self.limits.MaxBufferSize()).err();
+        if descriptor.mappedAtCreation && size_error.is_some() {
+            return Err(Error::Range(
+                "Cannot allocate the mapping of an oversized buffer".to_owned(),
+            ));
+        }
+        let desc = wgt::BufferUsages::from_bits(descriptor.usage)
+            .filter(|_| size_error.is_none())
+            .map(|usg| wgpu_res::BufferDescriptor {
                 label: convert_label(&descriptor.parent),
                 size: descriptor.size as wgt::BufferAddress,
                 usage: usg,
@@ -376,7 +388,9 @@ impl GPUDeviceMethods for GPUDevice {
             .create_buffer_id(self.device.0.backend());
 
         let scope_id = self.use_current_scope();
-        if desc.is_none() {
+        if let Some(error) = size_error {
+            self.handle_server_msg(scope_id, WebGPUOpResult::ValidationError(error));
+        } else if desc.is_none() {
             self.handle_server_msg(
                 scope_id,
                 WebGPUOpResult::ValidationError(String::from("Invalid GPUBufferUsage")),
@@ -420,6 +434,7 @@ impl GPUDeviceMethods for GPUDevice {
             &self,
             state,
             descriptor.size,
+            descriptor.usage,
             map_info,
             descriptor.parent.label.clone().unwrap_or_default(),
         ))
@@ -183,6 +183,14 @@ impl HTMLStyleElement {
     }
 }
 
+/// Whether a `<style>` element has to update its style block after a change to
+/// its children or to its connectedness: it must be connected, in a document
+/// or in a shadow tree, and the parser must be done with it.
+/// <https://html.spec.whatwg.org/multipage/#update-a-style-block>
+pub fn should_update_style_block(connected: bool, in_stack_of_open_elements: bool) -> bool {
+    connected && !in_stack_of_open_elements
+}
+
 impl VirtualMethods for HTMLStyleElement {
     fn super_type(&self) -> Option<&dyn VirtualMethods> {
         Some(self.upcast::<HTMLElement>() as &dyn VirtualMethods)
@@ -195,8 +203,11 @@ impl VirtualMethods for HTMLStyleElement {
         // Handles the case when:
         // "The element is not on the stack of open elements of an HTML parser or XML parser,
         // and one of its child nodes is modified by a script."
-        // TODO: Handle Text child contents being mutated.
-        if self.upcast::<Node>().is_in_doc() && !self.in_stack_of_open_elements.get() {
+        // Text child contents being mutated are reported as `ChildrenMutation::ChangeText`.
+        if should_update_style_block(
+            self.upcast::<Node>().is_connected(),
+            self.in_stack_of_open_elements.get(),
+        ) {
             self.parse_own_css();
         }
     }
@@ -208,7 +219,7 @@ impl VirtualMethods for HTMLStyleElement {
         // Handles the case when:
         // "The element is not on the stack of open elements of an HTML parser or XML parser,
         // and it becomes connected or disconnected."
-        if context.tree_connected && !self.in_stack_of_open_elements.get() {
+        if should_update_style_block(context.tree_connected, self.in_stack_of_open_elements.get()) {
             self.parse_own_css();
         }
     }
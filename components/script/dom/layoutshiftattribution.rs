@@ -0,0 +1,88 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use euclid::default::Rect;
+
+use crate::dom::bindings::codegen::Bindings::LayoutShiftAttributionBinding::LayoutShiftAttributionMethods;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::domrectreadonly::DOMRectReadOnly;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::node::Node;
+
+/// A node that moved as part of a layout shift, along with its visual
+/// representation before and after the shift.
+pub struct LayoutShiftSource<'a> {
+    pub node: Option<&'a Node>,
+    pub previous_rect: Rect<f64>,
+    pub current_rect: Rect<f64>,
+}
+
+#[dom_struct]
+pub struct LayoutShiftAttribution {
+    reflector_: Reflector,
+    node: Option<Dom<Node>>,
+    previous_rect: Dom<DOMRectReadOnly>,
+    current_rect: Dom<DOMRectReadOnly>,
+}
+
+impl LayoutShiftAttribution {
+    fn new_inherited(
+        node: Option<&Node>,
+        previous_rect: &DOMRectReadOnly,
+        current_rect: &DOMRectReadOnly,
+    ) -> LayoutShiftAttribution {
+        LayoutShiftAttribution {
+            reflector_: Reflector::new(),
+            node: node.map(Dom::from_ref),
+            previous_rect: Dom::from_ref(previous_rect),
+            current_rect: Dom::from_ref(current_rect),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        source: &LayoutShiftSource,
+    ) -> DomRoot<LayoutShiftAttribution> {
+        let previous_rect = rect_to_dom(global, &source.previous_rect);
+        let current_rect = rect_to_dom(global, &source.current_rect);
+        reflect_dom_object(
+            Box::new(LayoutShiftAttribution::new_inherited(
+                source.node,
+                &previous_rect,
+                &current_rect,
+            )),
+            global,
+        )
+    }
+}
+
+fn rect_to_dom(global: &GlobalScope, rect: &Rect<f64>) -> DomRoot<DOMRectReadOnly> {
+    DOMRectReadOnly::new(
+        global,
+        None,
+        rect.origin.x,
+        rect.origin.y,
+        rect.size.width,
+        rect.size.height,
+    )
+}
+
+impl LayoutShiftAttributionMethods for LayoutShiftAttribution {
+    // https://wicg.github.io/layout-instability/#dom-layoutshiftattribution-node
+    fn GetNode(&self) -> Option<DomRoot<Node>> {
+        self.node.as_ref().map(|node| DomRoot::from_ref(&**node))
+    }
+
+    // https://wicg.github.io/layout-instability/#dom-layoutshiftattribution-previousrect
+    fn PreviousRect(&self) -> DomRoot<DOMRectReadOnly> {
+        DomRoot::from_ref(&*self.previous_rect)
+    }
+
+    // https://wicg.github.io/layout-instability/#dom-layoutshiftattribution-currentrect
+    fn CurrentRect(&self) -> DomRoot<DOMRectReadOnly> {
+        DomRoot::from_ref(&*self.current_rect)
+    }
+}
@@ -67,32 +67,35 @@ impl MediaListMethods for MediaList {
 
     // https://drafts.csswg.org/cssom/#dom-medialist-mediatext
     fn SetMediaText(&self, value: DOMString) {
-        let mut guard = self.shared_lock().write();
-        let media_queries = self.media_queries.write_with(&mut guard);
-        // Step 2
-        if value.is_empty() {
-            // Step 1
-            *media_queries = StyleMediaList::empty();
-            return;
+        {
+            let mut guard = self.shared_lock().write();
+            let media_queries = self.media_queries.write_with(&mut guard);
+            // Step 2
+            if value.is_empty() {
+                // Step 1
+                *media_queries = StyleMediaList::empty();
+            } else {
+                // Step 3
+                let mut input = ParserInput::new(&value);
+                let mut parser = Parser::new(&mut input);
+                let global = self.global();
+                let window = global.as_window();
+                let url = window.get_url();
+                let quirks_mode = window.Document().quirks_mode();
+                let context = ParserContext::new(
+                    Origin::Author,
+                    &url,
+                    Some(CssRuleType::Media),
+                    ParsingMode::DEFAULT,
+                    quirks_mode,
+                    /* namespaces = */ Default::default(),
+                    window.css_error_reporter(),
+                    None,
+                );
+                *media_queries = StyleMediaList::parse(&context, &mut parser);
+            }
         }
-        // Step 3
-        let mut input = ParserInput::new(&value);
-        let mut parser = Parser::new(&mut input);
-        let global = self.global();
-        let window = global.as_window();
-        let url = window.get_url();
-        let quirks_mode = window.Document().quirks_mode();
-        let context = ParserContext::new(
-            Origin::Author,
-            &url,
-            Some(CssRuleType::Media),
-            ParsingMode::DEFAULT,
-            quirks_mode,
-            /* namespaces = */ Default::default(),
-            window.css_error_reporter(),
-            None,
-        );
-        *media_queries = StyleMediaList::parse(&context, &mut parser);
+        self.parent_stylesheet.invalidate_owner_stylesheets();
     }
 
     // https://drafts.csswg.org/cssom/#dom-medialist-length
@@ -153,6 +156,8 @@ impl MediaListMethods for MediaList {
         }
         // Step 4
         mq.media_queries.push(m.unwrap());
+        drop(guard);
+        self.parent_stylesheet.invalidate_owner_stylesheets();
     }
 
     // https://drafts.csswg.org/cssom/#dom-medialist-deletemedium
@@ -189,5 +194,7 @@ impl MediaListMethods for MediaList {
             .filter(|q| m_serialized != q.to_css_string())
             .collect();
         media_list.media_queries = new_vec;
+        drop(guard);
+        self.parent_stylesheet.invalidate_owner_stylesheets();
     }
 }
@@ -435,6 +435,8 @@ pub mod imagebitmap;
 pub mod imagedata;
 pub mod inputevent;
 pub mod keyboardevent;
+pub mod layoutshift;
+pub mod layoutshiftattribution;
 pub mod location;
 pub mod mediadeviceinfo;
 pub mod mediadevices;
@@ -4,30 +4,47 @@
 
 use std::cell::Cell;
 use std::cmp::Ordering;
+use std::collections::hash_map::{DefaultHasher, RandomState};
 use std::collections::VecDeque;
+use std::hash::{BuildHasher, Hasher};
+use std::ops::Deref;
+use std::ptr;
+use std::rc::Rc;
 
 use dom_struct::dom_struct;
+use lazy_static::lazy_static;
+use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 use metrics::ToMs;
+use servo_config::pref;
+use servo_url::ImmutableOrigin;
 
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::PerformanceBinding::{
-    DOMHighResTimeStamp, PerformanceEntryList as DOMPerformanceEntryList, PerformanceMethods,
+    DOMHighResTimeStamp, MemoryAttribution, MemoryBreakdownEntry, MemoryMeasurement,
+    PerformanceEntryList as DOMPerformanceEntryList, PerformanceMethods,
 };
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
-use crate::dom::bindings::root::DomRoot;
-use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::dedicatedworkerglobalscope::DedicatedWorkerGlobalScope;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::layoutshift::LayoutShift;
+use crate::dom::layoutshiftattribution::LayoutShiftSource;
 use crate::dom::performanceentry::PerformanceEntry;
 use crate::dom::performancemark::PerformanceMark;
 use crate::dom::performancemeasure::PerformanceMeasure;
 use crate::dom::performancenavigation::PerformanceNavigation;
 use crate::dom::performancenavigationtiming::PerformanceNavigationTiming;
 use crate::dom::performanceobserver::PerformanceObserver as DOMPerformanceObserver;
+use crate::dom::promise::Promise;
+use crate::dom::serviceworkerglobalscope::ServiceWorkerGlobalScope;
 use crate::dom::window::Window;
+use crate::realms::InRealm;
 
 const INVALID_ENTRY_NAMES: &'static [&'static str] = &[
     "navigationStart",
@@ -71,22 +88,15 @@ impl PerformanceEntryList {
         name: Option<DOMString>,
         entry_type: Option<DOMString>,
     ) -> Vec<DomRoot<PerformanceEntry>> {
-        let mut res = self
-            .entries
-            .iter()
-            .filter(|e| {
-                name.as_ref().map_or(true, |name_| *e.name() == *name_) &&
-                    entry_type
-                        .as_ref()
-                        .map_or(true, |type_| *e.entry_type() == *type_)
-            })
-            .map(|e| e.clone())
-            .collect::<Vec<DomRoot<PerformanceEntry>>>();
-        res.sort_by(|a, b| {
-            a.start_time()
-                .partial_cmp(&b.start_time())
-                .unwrap_or(Ordering::Equal)
+        let mut res = copy_matching_entries(&self.entries, |e| {
+            entry_matches_filter(
+                e.name(),
+                e.entry_type(),
+                name.as_deref(),
+                entry_type.as_deref(),
+            )
         });
+        sort_by_start_time(&mut res, |entry| entry.start_time());
         res
     }
 
@@ -95,11 +105,13 @@ impl PerformanceEntryList {
         name: Option<DOMString>,
         entry_type: Option<DOMString>,
     ) {
-        self.entries.retain(|e| {
-            name.as_ref().map_or(true, |name_| *e.name() != *name_) &&
-                entry_type
-                    .as_ref()
-                    .map_or(true, |type_| *e.entry_type() != *type_)
+        remove_matching_entries(&mut self.entries, |e| {
+            entry_matches_filter(
+                e.name(),
+                e.entry_type(),
+                name.as_deref(),
+                entry_type.as_deref(),
+            )
         });
     }
 
@@ -149,10 +161,17 @@ pub struct Performance {
     resource_timing_buffer_current_size: Cell<usize>,
     resource_timing_buffer_pending_full_event: Cell<bool>,
     resource_timing_secondary_entries: DomRefCell<VecDeque<DomRoot<PerformanceEntry>>>,
+    /// The per-origin seed used to jitter `now()`, if timing jitter is enabled.
+    timing_jitter_seed: Option<u64>,
+    /// What `timing` returned the first time, which it keeps returning.
+    timing: MutNullableDom<PerformanceNavigationTiming>,
 }
 
 impl Performance {
-    fn new_inherited(navigation_start_precise: u64) -> Performance {
+    fn new_inherited(
+        navigation_start_precise: u64,
+        timing_jitter_seed: Option<u64>,
+    ) -> Performance {
         Performance {
             eventtarget: EventTarget::new_inherited(),
             buffer: DomRefCell::new(PerformanceEntryList::new(Vec::new())),
@@ -163,12 +182,25 @@ impl Performance {
             resource_timing_buffer_current_size: Cell::new(0),
             resource_timing_buffer_pending_full_event: Cell::new(false),
             resource_timing_secondary_entries: DomRefCell::new(VecDeque::new()),
+            timing_jitter_seed,
+            timing: MutNullableDom::new(None),
         }
     }
 
     pub fn new(global: &GlobalScope, navigation_start_precise: u64) -> DomRoot<Performance> {
+        let timing_jitter_seed = if pref!(dom.performance.timing_jitter.enabled) {
+            Some(timing_jitter_seed(
+                &TIMING_JITTER_KEY,
+                global.origin().immutable(),
+            ))
+        } else {
+            None
+        };
         reflect_dom_object(
-            Box::new(Performance::new_inherited(navigation_start_precise)),
+            Box::new(Performance::new_inherited(
+                navigation_start_precise,
+                timing_jitter_seed,
+            )),
             global,
         )
     }
@@ -176,10 +208,18 @@ impl Performance {
     /// Clear all buffered performance entries, and disable the buffer.
     /// Called as part of the window's "clear_js_runtime" workflow,
     /// performed when exiting a pipeline.
+    ///
+    /// The observers and the resource timing secondary buffer are dropped
+    /// too, so that none of them keeps entries alive past the pipeline exit.
     pub fn clear_and_disable_performance_entry_buffer(&self) {
-        let mut buffer = self.buffer.borrow_mut();
-        buffer.entries.clear();
+        release_performance_entries(
+            &mut self.buffer.borrow_mut().entries,
+            &mut self.resource_timing_secondary_entries.borrow_mut(),
+            &mut self.observers.borrow_mut(),
+            |observer| observer.observer.set_entries(Vec::new()),
+        );
         self.resource_timing_buffer_size_limit.set(0);
+        self.resource_timing_buffer_current_size.set(0);
     }
 
     /// Add a PerformanceObserver to the list of observers with a set of
@@ -219,11 +259,7 @@ impl Performance {
                 observer.set_entries(obs_entries);
             }
 
-            if !self.pending_notification_observers_task.get() {
-                self.pending_notification_observers_task.set(true);
-                let task_source = self.global().performance_timeline_task_source();
-                task_source.queue_notification(&self.global());
-            }
+            self.queue_notification_task();
         }
         let mut observers = self.observers.borrow_mut();
         match observers.iter().position(|o| *o.observer == *observer) {
@@ -263,11 +299,31 @@ impl Performance {
     /// Also this algorithm has been extented according to :
     /// <https://w3c.github.io/resource-timing/#sec-extensions-performance-interface>
     pub fn queue_entry(&self, entry: &PerformanceEntry) -> Option<usize> {
+        // Queuing the same resource entry twice in a row is always a bug, and would
+        // count it twice against the resource timing buffer size limit.
+        if entry.entry_type() == "resource" && self.is_last_buffered_entry(entry) {
+            warn!(
+                "Resource timing entry for {} was queued twice",
+                entry.name()
+            );
+            return None;
+        }
+
         // https://w3c.github.io/performance-timeline/#dfn-determine-eligibility-for-adding-a-performance-entry
         if entry.entry_type() == "resource" && !self.should_queue_resource_entry(entry) {
             return None;
         }
+        self.queue_eligible_entry(entry)
+    }
 
+    fn is_last_buffered_entry(&self, entry: &PerformanceEntry) -> bool {
+        is_last_entry(&self.buffer.borrow().entries, entry)
+    }
+
+    /// Queue an entry that is known to be eligible for the performance entry
+    /// buffer, i.e. steps 1-6 of
+    /// <https://w3c.github.io/performance-timeline/#queue-a-performanceentry>.
+    fn queue_eligible_entry(&self, entry: &PerformanceEntry) -> Option<usize> {
         // Steps 1-3.
         // Add the performance entry to the list of performance entries that have not
         // been notified to each performance observer owner, filtering the ones it's
@@ -290,19 +346,32 @@ impl Performance {
 
         let entry_last_index = self.buffer.borrow_mut().entries.len() - 1;
 
+        // Steps 5 and 6.
+        if !self.queue_notification_task() {
+            return None;
+        }
+
+        Some(entry_last_index)
+    }
+
+    /// Queue the observers notification task, unless one is already queued
+    /// and hasn't run yet, in which case it will also deliver whatever was
+    /// queued since. Returns whether a new task was queued.
+    ///
+    /// Steps 5 and 6 of
+    /// <https://w3c.github.io/performance-timeline/#queue-a-performanceentry>.
+    fn queue_notification_task(&self) -> bool {
         // Step 5.
         // If there is already a queued notification task, we just bail out.
-        if self.pending_notification_observers_task.get() {
-            return None;
+        if !claim_notification_task(&self.pending_notification_observers_task) {
+            return false;
         }
 
         // Step 6.
         // Queue a new notification task.
-        self.pending_notification_observers_task.set(true);
         let task_source = self.global().performance_timeline_task_source();
         task_source.queue_notification(&self.global());
-
-        Some(entry_last_index)
+        true
     }
 
     /// Observers notifications task.
@@ -335,23 +404,82 @@ impl Performance {
         (time::precise_time_ns() - self.navigation_start_precise).to_ms()
     }
 
+    /// Convert `relative`, a time in milliseconds relative to the time origin
+    /// (as returned by `performance.now()`), to milliseconds since the unix epoch.
+    // No caller yet: nothing in the tree reports performance.now() times
+    // as wall-clock times.
+    #[allow(dead_code)]
+    pub fn hr_time_to_unix_ms(&self, relative: f64) -> f64 {
+        let current_time = time::get_time();
+        let now_unix_ms = current_time.sec as f64 * 1000. + current_time.nsec as f64 / 1000000.;
+        relative_time_to_unix_ms(
+            relative,
+            self.navigation_start_precise,
+            time::precise_time_ns(),
+            now_unix_ms,
+        )
+    }
+
+    /// Queue a `layout-shift` entry for a layout shift of the given score,
+    /// as reported by layout.
+    ///
+    /// Layout does not detect layout shifts yet, so for now the only caller is
+    /// `TestBinding.queueLayoutShift()`.
+    ///
+    /// <https://wicg.github.io/layout-instability/#report-the-layout-shift>
+    pub fn queue_layout_shift(
+        &self,
+        value: f64,
+        had_recent_input: bool,
+        last_input_time: f64,
+        sources: &[LayoutShiftSource],
+    ) {
+        if !pref!(dom.performance.layout_shift.enabled) {
+            return;
+        }
+        let entry = LayoutShift::new(
+            &self.global(),
+            self.now(),
+            value,
+            had_recent_input,
+            last_input_time,
+            sources,
+        );
+        self.queue_entry(entry.upcast::<PerformanceEntry>());
+    }
+
+    // https://w3c.github.io/resource-timing/#dfn-can-add-resource-timing-entry
     fn can_add_resource_timing_entry(&self) -> bool {
-        self.resource_timing_buffer_current_size.get() <=
-            self.resource_timing_buffer_size_limit.get()
+        can_add_resource_timing_entry(
+            self.resource_timing_buffer_current_size.get(),
+            self.resource_timing_buffer_size_limit.get(),
+        )
     }
+
+    // https://w3c.github.io/resource-timing/#dfn-copy-secondary-buffer
     fn copy_secondary_resource_timing_buffer(&self) {
-        while self.can_add_resource_timing_entry() {
+        let count = resource_timing_entries_to_copy(
+            self.resource_timing_buffer_current_size.get(),
+            self.resource_timing_buffer_size_limit.get(),
+            self.resource_timing_secondary_entries.borrow().len(),
+        );
+        for _ in 0..count {
+            // Steps 1.1 and 1.4.
             let entry = self
                 .resource_timing_secondary_entries
                 .borrow_mut()
-                .pop_front();
-            if let Some(ref entry) = entry {
-                self.queue_entry(entry);
-            } else {
-                break;
-            }
+                .pop_front()
+                .expect("Counted more entries than the secondary buffer holds");
+            // Step 1.2. These entries have already been found eligible, so they
+            // must not go through `should_queue_resource_entry` again, which
+            // would only put them back in the secondary buffer.
+            self.queue_eligible_entry(&entry);
+            // Step 1.3.
+            self.resource_timing_buffer_current_size
+                .set(self.resource_timing_buffer_current_size.get() + 1);
         }
     }
+
     // `fire a buffer full event` paragraph of
     // https://w3c.github.io/resource-timing/#sec-extensions-performance-interface
     fn fire_buffer_full_event(&self) {
@@ -387,7 +515,14 @@ impl Performance {
             }
             // Step 3.
             self.resource_timing_buffer_pending_full_event.set(true);
+            // Steps 4 and 5. The event is fired synchronously rather than from a
+            // queued task, so the entry has to be in the secondary buffer first
+            // for a handler that makes room to see it copied over.
+            self.resource_timing_secondary_entries
+                .borrow_mut()
+                .push_back(DomRoot::from_ref(entry));
             self.fire_buffer_full_event();
+            return false;
         }
         // Steps 4 and 5.
         self.resource_timing_secondary_entries
@@ -401,21 +536,55 @@ impl Performance {
             *e = DomRoot::from_ref(entry);
         }
     }
+
+    /// Estimate the memory used by the DOM objects of this global, using the
+    /// same `MallocSizeOf` measurements as the memory profiler. The JS heap is
+    /// shared by the whole script thread, so it isn't attributed to the global.
+    ///
+    /// <https://wicg.github.io/performance-measure-memory/#measure-memory>
+    fn measure_memory(&self) -> MemoryMeasurement {
+        let global = self.global();
+        let mut ops = MallocSizeOfOps::new(servo_allocator::usable_size, None, None);
+        let (bytes, scope) = if let Some(window) = global.downcast::<Window>() {
+            let bytes = window.size_of(&mut ops) + window.Document().size_of(&mut ops);
+            (bytes, "Window")
+        } else if let Some(worker) = global.downcast::<DedicatedWorkerGlobalScope>() {
+            (worker.size_of(&mut ops), "DedicatedWorkerGlobalScope")
+        } else if let Some(worker) = global.downcast::<ServiceWorkerGlobalScope>() {
+            (worker.size_of(&mut ops), "ServiceWorkerGlobalScope")
+        } else {
+            (global.size_of(&mut ops), "")
+        };
+        memory_measurement(bytes as u64, global.get_url().into_string(), scope)
+    }
 }
 
 impl PerformanceMethods for Performance {
     // FIXME(avada): this should be deprecated in the future, but some sites still use it
     // https://dvcs.w3.org/hg/webperf/raw-file/tip/specs/NavigationTiming/Overview.html#performance-timing-attribute
     fn Timing(&self) -> DomRoot<PerformanceNavigationTiming> {
-        let entries = self.GetEntriesByType(DOMString::from("navigation"));
-        if entries.len() > 0 {
-            return DomRoot::from_ref(
-                entries[0]
-                    .downcast::<PerformanceNavigationTiming>()
-                    .unwrap(),
-            );
+        // Keep returning the object returned the first time. Every candidate
+        // reads its timings from the same document, so this loses nothing.
+        if let Some(timing) = self.timing.get() {
+            return timing;
         }
-        unreachable!("Are we trying to expose Performance.timing in workers?");
+        let timing = self
+            .GetEntriesByType(DOMString::from("navigation"))
+            .iter()
+            .find_map(|entry| entry.downcast::<PerformanceNavigationTiming>())
+            .map(DomRoot::from_ref)
+            .unwrap_or_else(|| {
+                // The entry is only submitted once the document's response has
+                // been received, and never for documents that aren't loaded by
+                // a parser context, such as the initial about:blank. Fall back
+                // to timings read straight from the document; `timing` is only
+                // exposed on Window.
+                let global = self.global();
+                let document = global.as_window().Document();
+                PerformanceNavigationTiming::new(&global, 0, 0, &document)
+            });
+        self.timing.set(Some(&timing));
+        timing
     }
 
     // https://w3c.github.io/navigation-timing/#dom-performance-navigation
@@ -425,7 +594,10 @@ impl PerformanceMethods for Performance {
 
     // https://dvcs.w3.org/hg/webperf/raw-file/tip/specs/HighResolutionTime/Overview.html#dom-performance-now
     fn Now(&self) -> DOMHighResTimeStamp {
-        reduce_timing_resolution(self.now())
+        match self.timing_jitter_seed {
+            Some(seed) => Finite::wrap(jittered_timing_resolution(self.now(), seed)),
+            None => reduce_timing_resolution(self.now()),
+        }
     }
 
     // https://www.w3.org/TR/hr-time-2/#dom-performance-timeorigin
@@ -477,6 +649,10 @@ impl PerformanceMethods for Performance {
 
     // https://w3c.github.io/user-timing/#dom-performance-clearmarks
     fn ClearMarks(&self, mark_name: Option<DOMString>) {
+        // Only the performance entry buffer is cleared. Marks already queued
+        // on or delivered to observers, including the ones replayed to
+        // `buffered` observers, are theirs to keep, see
+        // `remove_matching_entries()`.
         self.buffer
             .borrow_mut()
             .clear_entries_by_name_and_type(mark_name, Some(DOMString::from("mark")));
@@ -508,6 +684,8 @@ impl PerformanceMethods for Performance {
         };
 
         // Steps 4 to 8.
+        // When the end mark precedes the start mark the duration is negative,
+        // which the spec leaves as is rather than clamping to 0.
         let entry = PerformanceMeasure::new(
             &self.global(),
             measure_name,
@@ -534,6 +712,10 @@ impl PerformanceMethods for Performance {
             .borrow_mut()
             .clear_entries_by_name_and_type(None, Some(DOMString::from("resource")));
         self.resource_timing_buffer_current_size.set(0);
+        clear_stale_secondary_entries(
+            &mut self.resource_timing_secondary_entries.borrow_mut(),
+            self.resource_timing_buffer_pending_full_event.get(),
+        );
     }
 
     // https://w3c.github.io/resource-timing/#dom-performance-setresourcetimingbuffersize
@@ -542,6 +724,20 @@ impl PerformanceMethods for Performance {
             .set(max_size as usize);
     }
 
+    // https://wicg.github.io/performance-measure-memory/#dom-performance-measureuseragentspecificmemory
+    fn MeasureUserAgentSpecificMemory(&self, comp: InRealm) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new_in_current_realm(comp);
+        // Steps 2-6.
+        match measure_memory_if_isolated(global.is_cross_origin_isolated(), || {
+            self.measure_memory()
+        }) {
+            Ok(measurement) => promise.resolve_native(&measurement),
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+
     // https://w3c.github.io/resource-timing/#dom-performance-onresourcetimingbufferfull
     event_handler!(
         resourcetimingbufferfull,
@@ -550,6 +746,155 @@ impl PerformanceMethods for Performance {
     );
 }
 
+/// Drop every reference to performance entries held by the entry `buffer`,
+/// the resource timing `secondary` buffer and the `observers`, whose own
+/// pending entries are dropped by `release_observer` as each is removed.
+pub fn release_performance_entries<E, O>(
+    buffer: &mut Vec<E>,
+    secondary: &mut VecDeque<E>,
+    observers: &mut Vec<O>,
+    release_observer: impl FnMut(O),
+) {
+    buffer.clear();
+    secondary.clear();
+    observers.drain(..).for_each(release_observer);
+}
+
+/// The result of `measureUserAgentSpecificMemory()`: a `SecurityError`
+/// unless the global is cross-origin isolated, in which case `measure` is
+/// called. Memory is never measured for a global that isn't isolated.
+///
+/// <https://wicg.github.io/performance-measure-memory/#dom-performance-measureuseragentspecificmemory>
+pub fn measure_memory_if_isolated(
+    cross_origin_isolated: bool,
+    measure: impl FnOnce() -> MemoryMeasurement,
+) -> Fallible<MemoryMeasurement> {
+    // Step 2.
+    if !cross_origin_isolated {
+        return Err(Error::Security);
+    }
+    // Steps 3-6.
+    Ok(measure())
+}
+
+/// A measurement of `bytes` attributed, as a single DOM breakdown entry, to
+/// the global at `url` whose global object interface is `scope`.
+///
+/// <https://wicg.github.io/performance-measure-memory/#dictdef-memorymeasurement>
+pub fn memory_measurement(bytes: u64, url: String, scope: &str) -> MemoryMeasurement {
+    let attribution = MemoryAttribution {
+        url: Some(USVString(url)),
+        scope: Some(DOMString::from(scope)),
+    };
+    let breakdown = MemoryBreakdownEntry {
+        bytes: Some(bytes),
+        attribution: Some(vec![attribution]),
+        types: Some(vec![DOMString::from("DOM")]),
+    };
+    MemoryMeasurement {
+        bytes: Some(bytes),
+        breakdown: Some(vec![breakdown]),
+    }
+}
+
+/// Whether `entry` is the very object last in `entries`. Object identity, not
+/// equality, is what marks an entry queued twice: two fetches of the same URL
+/// can have equal entries.
+pub fn is_last_entry<T, E: Deref<Target = T>>(entries: &[E], entry: &T) -> bool {
+    entries.last().map_or(false, |last| ptr::eq(&**last, entry))
+}
+
+/// Mark the observers notification task as queued, returning whether the
+/// caller should queue it: only if `pending` wasn't already set by a task that
+/// hasn't run yet. `notify_observers()` clears `pending` when the task runs.
+///
+/// Steps 5 and 6 of
+/// <https://w3c.github.io/performance-timeline/#queue-a-performanceentry>.
+pub fn claim_notification_task(pending: &Cell<bool>) -> bool {
+    !pending.replace(true)
+}
+
+/// Whether the resource timing buffer, holding `current_size` entries, has room
+/// for another one under `size_limit`. A limit of 0 accepts no entries.
+///
+/// <https://w3c.github.io/resource-timing/#dfn-can-add-resource-timing-entry>
+pub fn can_add_resource_timing_entry(current_size: usize, size_limit: usize) -> bool {
+    current_size < size_limit
+}
+
+/// How many of the `secondary_len` entries waiting in the secondary buffer can
+/// be copied into a resource timing buffer holding `current_size` entries,
+/// e.g. after a `resourcetimingbufferfull` handler grew `size_limit`.
+///
+/// <https://w3c.github.io/resource-timing/#dfn-copy-secondary-buffer>
+pub fn resource_timing_entries_to_copy(
+    current_size: usize,
+    size_limit: usize,
+    secondary_len: usize,
+) -> usize {
+    secondary_len.min(size_limit.saturating_sub(current_size))
+}
+
+/// The part of `clearResourceTimings()` that deals with the secondary buffer.
+/// A `resourcetimingbufferfull` handler, run while `pending_full_event` is
+/// set, clears the buffer to make room for the secondary buffer's entries, so
+/// they are kept. Outside of one, they are stale and dropped, so they can't be
+/// copied into the buffer later.
+pub fn clear_stale_secondary_entries<T>(secondary: &mut VecDeque<T>, pending_full_event: bool) {
+    if !pending_full_event {
+        secondary.clear();
+    }
+}
+
+/// Sort `entries` by their `start_time`, as `getEntries()` returns them.
+/// The sort is stable, so entries with the same start time stay in insertion
+/// order.
+pub fn sort_by_start_time<T>(entries: &mut [T], start_time: impl Fn(&T) -> f64) {
+    entries.sort_by(|a, b| {
+        start_time(a)
+            .partial_cmp(&start_time(b))
+            .unwrap_or(Ordering::Equal)
+    });
+}
+
+/// Copies of the entries of `entries` selected by `matches`, like the ones
+/// `getEntriesByType()` returns and a `buffered` observer is replayed.
+pub fn copy_matching_entries<E: Clone>(entries: &[E], matches: impl Fn(&E) -> bool) -> Vec<E> {
+    entries.iter().filter(|e| matches(e)).cloned().collect()
+}
+
+/// Remove the entries of `entries` selected by `matches`, as `clearMarks()`
+/// and the other `clear*()` methods do. Copies made by
+/// `copy_matching_entries()`, such as the entries an observer already
+/// received, keep them.
+pub fn remove_matching_entries<E>(entries: &mut Vec<E>, matches: impl Fn(&E) -> bool) {
+    entries.retain(|e| !matches(e))
+}
+
+/// Whether an entry called `entry_name` of type `entry_type` is selected by
+/// the optional `name` and `type_` filters; a missing filter matches any entry.
+pub fn entry_matches_filter(
+    entry_name: &str,
+    entry_type: &str,
+    name: Option<&str>,
+    type_: Option<&str>,
+) -> bool {
+    name.map_or(true, |name| entry_name == name) && type_.map_or(true, |type_| entry_type == type_)
+}
+
+/// Convert `relative`, in milliseconds since the time origin
+/// `origin_precise_ns`, to milliseconds since the unix epoch, given readings
+/// of the precise clock and of the wall clock taken at the same instant.
+pub fn relative_time_to_unix_ms(
+    relative: f64,
+    origin_precise_ns: u64,
+    now_precise_ns: u64,
+    now_unix_ms: f64,
+) -> f64 {
+    let origin_unix_ms = now_unix_ms - (now_precise_ns - origin_precise_ns).to_ms();
+    origin_unix_ms + relative
+}
+
 // https://www.w3.org/TR/hr-time-2/#clock-resolution
 pub fn reduce_timing_resolution(exact: f64) -> DOMHighResTimeStamp {
     // We need a granularity no finer than 5 microseconds.
@@ -560,3 +905,38 @@ pub fn reduce_timing_resolution(exact: f64) -> DOMHighResTimeStamp {
     // is 10, which is 1/100th of a millisecond.
     Finite::wrap((exact * 100.0).floor() / 100.0)
 }
+
+lazy_static! {
+    /// The per-process secret keying the timing jitter seeds.
+    static ref TIMING_JITTER_KEY: RandomState = RandomState::new();
+}
+
+/// The seed `jittered_timing_resolution` uses for `origin`. It is keyed with
+/// the secret `key`, as a page that can compute the seed of its origin can
+/// compute every threshold, and recover sub-bucket timing from when `now()`
+/// crosses them. Each opaque origin is unique, and so is its seed.
+pub fn timing_jitter_seed(key: &RandomState, origin: &ImmutableOrigin) -> u64 {
+    key.hash_one(origin)
+}
+
+/// Coarsen `exact` to the same 10 microsecond buckets as
+/// `reduce_timing_resolution`, but round up to the next bucket when the
+/// position within the bucket is past a threshold derived from `seed` and
+/// the bucket itself.
+///
+/// The threshold is fixed for a given seed and bucket, so the result is
+/// deterministic and never decreases as `exact` increases.
+pub fn jittered_timing_resolution(exact: f64, seed: u64) -> f64 {
+    let scaled = exact * 100.0;
+    let bucket = scaled.floor();
+    let mut hasher = DefaultHasher::new();
+    hasher.write_u64(seed);
+    hasher.write_u64(bucket.to_bits());
+    // The top 53 bits of the hash, as a fraction in [0, 1).
+    let threshold = (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64;
+    if scaled - bucket >= threshold {
+        (bucket + 1.0) / 100.0
+    } else {
+        bucket / 100.0
+    }
+}
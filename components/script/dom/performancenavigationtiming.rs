@@ -117,9 +117,43 @@ impl PerformanceNavigationTimingMethods for PerformanceNavigationTiming {
         self.document.get_redirect_count()
     }
 
+    // https://wicg.github.io/nav-speculation/prerendering.html#dom-performancenavigationtiming-activationstart
+    fn ActivationStart(&self) -> DOMHighResTimeStamp {
+        // Servo doesn't prerender documents, so none of them is ever activated.
+        Finite::wrap(activation_start(None, self.navigation_start))
+    }
+
     // check-tidy: no specs after this line
     // Servo-only timing for when top-level content (not iframes) is complete
     fn TopLevelDomComplete(&self) -> DOMHighResTimeStamp {
         Finite::wrap(self.document.get_top_level_dom_complete() as f64)
     }
 }
+
+/// The `activationStart` of a navigation that started at `navigation_start`:
+/// how long after that the prerendered document was activated, at
+/// `activation_time`, or 0 for a document that wasn't prerendered.
+///
+/// <https://wicg.github.io/nav-speculation/prerendering.html#dom-performancenavigationtiming-activationstart>
+pub fn activation_start(activation_time: Option<u64>, navigation_start: u64) -> f64 {
+    activation_time.map_or(0., |activation_time| {
+        activation_time.saturating_sub(navigation_start) as f64
+    })
+}
+
+/// The `unloadEventStart` and `unloadEventEnd` a document reports for the
+/// unload event of the document it replaced, which was dispatched between
+/// `fired_start` and `fired_end`. They stay 0 unless both documents are same
+/// origin, as for a navigation with no previous document.
+///
+/// <https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-unloadeventstart>
+pub fn previous_document_unload_timings(
+    same_origin: bool,
+    fired_start: u64,
+    fired_end: u64,
+) -> (u64, u64) {
+    if !same_origin {
+        return (0, 0);
+    }
+    (fired_start, fired_end)
+}
@@ -8,6 +8,7 @@ use std::rc::Rc;
 use dom_struct::dom_struct;
 use js::jsval::JSVal;
 use js::rust::HandleObject;
+use servo_config::pref;
 
 use crate::dom::bindings::callback::ExceptionHandling;
 use crate::dom::bindings::cell::DomRefCell;
@@ -21,7 +22,7 @@ use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
 use crate::dom::console::Console;
 use crate::dom::globalscope::GlobalScope;
-use crate::dom::performance::PerformanceEntryList;
+use crate::dom::performance::{sort_by_start_time, PerformanceEntryList};
 use crate::dom::performanceentry::PerformanceEntry;
 use crate::dom::performanceobserverentrylist::PerformanceObserverEntryList;
 use crate::script_runtime::JSContext;
@@ -29,14 +30,39 @@ use crate::script_runtime::JSContext;
 /// List of allowed performance entry types, in alphabetical order.
 pub const VALID_ENTRY_TYPES: &'static [&'static str] = &[
     // "frame", //TODO Frame Timing API
-    "mark",       // User Timing API
-    "measure",    // User Timing API
-    "navigation", // Navigation Timing API
-    "paint",      // Paint Timing API
-    "resource",   // Resource Timing API
-                  // "server", XXX Server Timing API
+    "layout-shift", // Layout Instability API
+    "mark",         // User Timing API
+    "measure",      // User Timing API
+    "navigation",   // Navigation Timing API
+    "paint",        // Paint Timing API
+    "resource",     // Resource Timing API
+                    // "server", XXX Server Timing API
 ];
 
+/// Whether the API behind `entry_type` is enabled by its pref, if it has one.
+pub fn entry_type_is_enabled(entry_type: &str) -> bool {
+    match entry_type {
+        "layout-shift" => pref!(dom.performance.layout_shift.enabled),
+        _ => true,
+    }
+}
+
+/// The entry types of `VALID_ENTRY_TYPES` that are enabled according to
+/// `is_enabled`, in the same order.
+pub fn supported_entry_types(is_enabled: impl Fn(&str) -> bool) -> Vec<&'static str> {
+    VALID_ENTRY_TYPES
+        .iter()
+        .copied()
+        .filter(|entry_type| is_enabled(entry_type))
+        .collect()
+}
+
+/// Whether `entry_type` is one of the supported entry types, which
+/// `observe()` ignores the others of.
+pub fn is_supported_entry_type(entry_type: &str) -> bool {
+    VALID_ENTRY_TYPES.contains(&entry_type) && entry_type_is_enabled(entry_type)
+}
+
 #[derive(Clone, Copy, JSTraceable, MallocSizeOf, PartialEq)]
 enum ObserverType {
     Undefined,
@@ -185,7 +211,7 @@ impl PerformanceObserverMethods for PerformanceObserver {
             // Steps 6.1 - 6.2
             let entry_types = entry_types
                 .iter()
-                .filter(|e| VALID_ENTRY_TYPES.contains(&e.as_ref()))
+                .filter(|e| is_supported_entry_type(e))
                 .map(|e| e.clone())
                 .collect::<Vec<DOMString>>();
 
@@ -209,7 +235,7 @@ impl PerformanceObserverMethods for PerformanceObserver {
             Ok(())
         } else if let Some(entry_type) = &options.type_ {
             // Step 7.2
-            if !VALID_ENTRY_TYPES.contains(&entry_type.as_ref()) {
+            if !is_supported_entry_type(entry_type) {
                 Console::Warn(
                     &*self.global(),
                     vec![DOMString::from(
@@ -243,11 +269,13 @@ impl PerformanceObserverMethods for PerformanceObserver {
     // https://w3c.github.io/performance-timeline/#takerecords-method
     fn TakeRecords(&self) -> Vec<DomRoot<PerformanceEntry>> {
         let mut entries = self.entries.borrow_mut();
-        let taken = entries
+        let mut taken: Vec<_> = entries
             .iter()
             .map(|entry| DomRoot::from_ref(&**entry))
             .collect();
         entries.clear();
+        // Same order as getEntries(), not the order the entries were queued in.
+        sort_by_start_time(&mut taken, |entry| entry.start_time());
         return taken;
     }
 }
@@ -52,10 +52,10 @@ pub struct PerformanceResourceTiming {
     transfer_size: u64,     //size in octets
     encoded_body_size: u64, //size in octets
     decoded_body_size: u64, //size in octets
+    response_status: u16,
 }
 
 // TODO(#21269): next_hop
-// TODO(#21264): worker_start
 // TODO(#21258): fetch_start
 // TODO(#21259): domain_lookup_start
 // TODO(#21260): domain_lookup_end
@@ -97,17 +97,20 @@ impl PerformanceResourceTiming {
             transfer_size: 0,
             encoded_body_size: 0,
             decoded_body_size: 0,
+            response_status: 0,
         }
     }
 
     //TODO fetch start should be in RFT
     #[allow(crown::unrooted_must_root)]
-    fn from_resource_timing(
+    pub fn from_resource_timing(
         url: ServoUrl,
         initiator_type: InitiatorType,
         next_hop: Option<DOMString>,
         resource_timing: &ResourceFetchTiming,
     ) -> PerformanceResourceTiming {
+        // ResourceFetchTiming already hides what the timing allow check
+        // protects when it failed, see mark_timing_check_failed().
         PerformanceResourceTiming {
             entry: PerformanceEntry::new_inherited(
                 DOMString::from(url.into_string()),
@@ -117,7 +120,7 @@ impl PerformanceResourceTiming {
             ),
             initiator_type: initiator_type,
             next_hop: next_hop,
-            worker_start: 0.,
+            worker_start: resource_timing.worker_start as f64,
             redirect_start: resource_timing.redirect_start as f64,
             redirect_end: resource_timing.redirect_end as f64,
             fetch_start: resource_timing.fetch_start as f64,
@@ -130,9 +133,10 @@ impl PerformanceResourceTiming {
             request_start: resource_timing.request_start as f64,
             response_start: resource_timing.response_start as f64,
             response_end: resource_timing.response_end as f64,
-            transfer_size: 0,
-            encoded_body_size: 0,
-            decoded_body_size: 0,
+            transfer_size: resource_timing.transfer_size,
+            encoded_body_size: resource_timing.encoded_body_size,
+            decoded_body_size: resource_timing.decoded_body_size,
+            response_status: resource_timing.response_status,
         }
     }
 
@@ -208,11 +212,21 @@ impl PerformanceResourceTimingMethods for PerformanceResourceTiming {
         self.decoded_body_size
     }
 
+    // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-responsestatus
+    fn ResponseStatus(&self) -> u16 {
+        self.response_status
+    }
+
     // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-requeststart
     fn RequestStart(&self) -> DOMHighResTimeStamp {
         reduce_timing_resolution(self.request_start)
     }
 
+    // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-workerstart
+    fn WorkerStart(&self) -> DOMHighResTimeStamp {
+        reduce_timing_resolution(self.worker_start)
+    }
+
     // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-redirectstart
     fn RedirectStart(&self) -> DOMHighResTimeStamp {
         reduce_timing_resolution(self.redirect_start)
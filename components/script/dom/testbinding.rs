@@ -1049,6 +1049,12 @@ impl TestBindingMethods for TestBinding {
         self.global().as_window().advance_animation_clock(ms);
     }
 
+    fn QueueLayoutShift(&self, value: Finite<f64>, had_recent_input: bool) {
+        self.global()
+            .performance()
+            .queue_layout_shift(*value, had_recent_input, 0., &[]);
+    }
+
     fn Panic(&self) {
         panic!("explicit panic from script")
     }
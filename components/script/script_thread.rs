@@ -2719,6 +2719,18 @@ impl ScriptThread {
         let document = self.documents.borrow().find_document(pipeline_id);
         if let Some(document) = document {
             document.unload(false);
+
+            // The document replacing this one in its browsing context reports
+            // the timings of the unload event we just fired.
+            let new_document = document
+                .window()
+                .undiscarded_window_proxy()
+                .and_then(|window_proxy| window_proxy.currently_active())
+                .filter(|active_id| *active_id != pipeline_id)
+                .and_then(|active_id| self.documents.borrow().find_document(active_id));
+            if let Some(new_document) = new_document {
+                new_document.set_previous_document_unload_timings(&document);
+            }
         }
     }
 
@@ -14,6 +14,81 @@ pub mod area {
     pub use crate::dom::htmlareaelement::{Area, Shape};
 }
 
+pub mod cssrulelist {
+    pub use crate::dom::bindings::error::Error;
+    pub use crate::dom::cssrulelist::check_rule_limit;
+}
+
+pub mod cssstylesheet {
+    pub use crate::dom::cssstylesheet::{send_style_sheet_change, StyleSheetOwner};
+}
+
+pub mod dommatrix {
+    pub use crate::dom::bindings::codegen::Bindings::DOMMatrixBinding::{
+        DOMMatrix2DInit, DOMMatrixInit,
+    };
+    pub use crate::dom::bindings::codegen::Bindings::DOMMatrixReadOnlyBinding::{
+        DOMMatrixBufferFormat, DOMMatrixReadOnlyMethods,
+    };
+    pub use crate::dom::bindings::error::Error;
+    pub use crate::dom::dommatrixreadonly::{
+        angle_argument_in_degrees, cached_rotation_quaternion, clone_matrix, component_keeps_2d,
+        compose_matrix, decompose_matrix, dommatrix2dinit_to_matrix, dommatrixinit_to_matrix,
+        entries_to_matrix, invert_matrix, matrix_2d_components, matrix_column,
+        matrix_entries_from_bytes, matrix_equals, matrix_from_row_major, matrix_from_rows,
+        matrix_hash, matrix_row, matrix_to_row_major, multiply_all, parse_angle_in_degrees,
+        post_multiply, pre_multiply, quaternion_to_matrix, serialize_matrix, set_matrix_component,
+        then_2d, transform_homogeneous_point, transform_points, transform_to_matrix,
+        transform_to_matrix_with_origin, translated_matrix, validate_scale_origin,
+        DOMMatrixReadOnly, MatrixOp, TransformCache,
+    };
+}
+
+pub mod gpudevice {
+    pub use crate::dom::bindings::codegen::Bindings::WebGPUBinding::{
+        GPUBufferDescriptor, GPUObjectDescriptorBase,
+    };
+    pub use crate::dom::bindings::str::USVString;
+    pub use crate::dom::gpudevice::convert_label;
+}
+
+pub mod gpubuffer {
+    pub use crate::dom::bindings::codegen::Bindings::WebGPUBinding::GPUMapModeConstants;
+    pub use crate::dom::bindings::error::Error;
+    pub use crate::dom::gpubuffer::{
+        debug_assert_buffer_state_transition, debug_mapped_ranges, destroy_step, finalize_mapping,
+        flush_range_bytes, flush_range_payload, is_map_read, is_valid_buffer_state_transition,
+        is_valid_map_range, map_async_state_error, map_range_size, mapped_buffer_info,
+        release_mapping_view, requested_mapped_range, retain_mapping_for_view,
+        state_after_map_response, unmap_payload, validate_buffer_size,
+        validate_mapped_at_creation_size, validate_mapped_range, write_back_ranges, DestroyStep,
+        GPUBufferMapInfo, GPUBufferState, MappedRangeError,
+    };
+}
+
+pub mod htmlstyleelement {
+    pub use crate::dom::htmlstyleelement::should_update_style_block;
+}
+
+pub mod performance {
+    pub use crate::dom::bindings::codegen::Bindings::PerformanceBinding::MemoryMeasurement;
+    pub use crate::dom::bindings::codegen::Bindings::PerformanceResourceTimingBinding::PerformanceResourceTimingMethods;
+    pub use crate::dom::bindings::error::Error;
+    pub use crate::dom::document::record_milestone;
+    pub use crate::dom::performance::{
+        can_add_resource_timing_entry, claim_notification_task, clear_stale_secondary_entries,
+        copy_matching_entries, entry_matches_filter, is_last_entry, jittered_timing_resolution,
+        measure_memory_if_isolated, memory_measurement, relative_time_to_unix_ms,
+        release_performance_entries, remove_matching_entries, resource_timing_entries_to_copy,
+        sort_by_start_time, timing_jitter_seed,
+    };
+    pub use crate::dom::performancenavigationtiming::{
+        activation_start, previous_document_unload_timings,
+    };
+    pub use crate::dom::performanceobserver::supported_entry_types;
+    pub use crate::dom::performanceresourcetiming::{InitiatorType, PerformanceResourceTiming};
+}
+
 #[allow(non_snake_case)]
 pub mod size_of {
     use std::mem::size_of;
@@ -96,6 +96,26 @@ pub enum ScriptToDevtoolsControlMsg {
 
     /// Report a page title change
     TitleChanged(PipelineId, String),
+
+    /// Report a change to the rules of a stylesheet made through the CSSOM
+    StyleSheetChanged(PipelineId, StyleSheetChange),
+}
+
+/// A change to the rules of a stylesheet, identified by the node owning it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StyleSheetChange {
+    /// The unique id of the owner node, as used by the inspector.
+    pub owner_node: Option<String>,
+    pub href: Option<String>,
+    pub kind: StyleSheetChangeKind,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum StyleSheetChangeKind {
+    /// A rule was inserted at the given index.
+    RuleInserted(u32),
+    /// The rule at the given index was deleted.
+    RuleDeleted(u32),
 }
 
 /// Serialized JS return values
@@ -509,6 +509,8 @@ pub struct ResourceFetchTiming {
     pub timing_type: ResourceTimingType,
     /// Number of redirects until final resource (currently limited to 20)
     pub redirect_count: u16,
+    /// When a service worker started handling the fetch, or 0 if none did.
+    pub worker_start: u64,
     pub request_start: u64,
     pub secure_connection_start: u64,
     pub response_start: u64,
@@ -519,6 +521,14 @@ pub struct ResourceFetchTiming {
     pub connect_start: u64,
     pub connect_end: u64,
     pub start_time: u64,
+    /// Size in octets of the response as received over the network, including headers.
+    pub transfer_size: u64,
+    /// Size in octets of the response body before removing any content coding.
+    pub encoded_body_size: u64,
+    /// Size in octets of the response body after removing any content coding.
+    pub decoded_body_size: u64,
+    /// The HTTP status of the response, or 0 if it is opaque.
+    pub response_status: u16,
 }
 
 pub enum RedirectStartValue {
@@ -543,6 +553,7 @@ pub enum ResourceTimeValue {
 
 pub enum ResourceAttribute {
     RedirectCount(u16),
+    WorkerStart,
     DomainLookupStart,
     RequestStart,
     ResponseStart,
@@ -571,6 +582,7 @@ impl ResourceFetchTiming {
             timing_check_passed: true,
             domain_lookup_start: 0,
             redirect_count: 0,
+            worker_start: 0,
             secure_connection_start: 0,
             request_start: 0,
             response_start: 0,
@@ -581,6 +593,10 @@ impl ResourceFetchTiming {
             connect_end: 0,
             response_end: 0,
             start_time: 0,
+            transfer_size: 0,
+            encoded_body_size: 0,
+            decoded_body_size: 0,
+            response_status: 0,
         }
     }
 
@@ -603,6 +619,7 @@ impl ResourceFetchTiming {
         match attribute {
             ResourceAttribute::DomainLookupStart => self.domain_lookup_start = now,
             ResourceAttribute::RedirectCount(count) => self.redirect_count = count,
+            ResourceAttribute::WorkerStart => self.worker_start = now,
             ResourceAttribute::RequestStart => self.request_start = now,
             ResourceAttribute::ResponseStart => self.response_start = now,
             ResourceAttribute::RedirectStart(val) => match val {
@@ -642,15 +659,48 @@ impl ResourceFetchTiming {
         }
     }
 
+    /// Record the HTTP status of a response fetched from the network. Unlike
+    /// the timings, it does not depend on the timing allow check, but an
+    /// opaque response must not reveal its status and reports 0.
+    /// <https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-responsestatus>
+    pub fn set_response_status(&mut self, status: u16, is_opaque: bool) {
+        self.response_status = if is_opaque { 0 } else { status };
+    }
+
+    /// Record the body sizes of a response fetched from the network. These are
+    /// only exposed when the timing allow check passed.
+    /// <https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-transfersize>
+    pub fn set_body_sizes(&mut self, encoded_body_size: u64, decoded_body_size: u64) {
+        if !self.timing_check_passed {
+            return;
+        }
+        // The spec leaves the size of the headers out of transferSize to avoid
+        // leaking them, and uses a fixed 300 octets instead.
+        self.transfer_size = encoded_body_size + 300;
+        self.encoded_body_size = encoded_body_size;
+        self.decoded_body_size = decoded_body_size;
+    }
+
+    /// Record that the timing allow check failed, clearing every attribute it
+    /// protects. From then on `set_attribute()` and `set_body_sizes()` don't
+    /// record them either, so this is the one place that hides them:
+    /// `PerformanceResourceTiming` exposes whatever is left as is.
+    /// <https://w3c.github.io/resource-timing/#sec-cross-origin-resources>
     pub fn mark_timing_check_failed(&mut self) {
         self.timing_check_passed = false;
         self.domain_lookup_start = 0;
         self.redirect_count = 0;
+        self.worker_start = 0;
         self.request_start = 0;
         self.response_start = 0;
         self.redirect_start = 0;
+        self.redirect_end = 0;
         self.connect_start = 0;
         self.connect_end = 0;
+        self.secure_connection_start = 0;
+        self.transfer_size = 0;
+        self.encoded_body_size = 0;
+        self.decoded_body_size = 0;
     }
 }
 
@@ -210,3 +210,100 @@ fn test_reset_start_time() {
         "failed to reset `start_time`"
     );
 }
+
+#[test]
+fn test_worker_start_defaults_to_zero() {
+    let resource_timing = ResourceFetchTiming::new(ResourceTimingType::Resource);
+    assert_eq!(
+        resource_timing.worker_start, 0,
+        "`worker_start` should be zero when no service worker handled the fetch"
+    );
+}
+
+#[test]
+fn test_set_worker_start() {
+    let mut resource_timing: ResourceFetchTiming =
+        ResourceFetchTiming::new(ResourceTimingType::Resource);
+
+    // verify that a service worker handling the fetch sets `worker_start`
+    resource_timing.set_attribute(ResourceAttribute::WorkerStart);
+    assert!(
+        resource_timing.worker_start > 0,
+        "failed to set `worker_start`"
+    );
+
+    // verify that `worker_start` is hidden when the timing allow check fails
+    resource_timing.mark_timing_check_failed();
+    assert_eq!(
+        resource_timing.worker_start, 0,
+        "`worker_start` should be zero without TAO"
+    );
+}
+
+#[test]
+fn test_timing_check_failure_hides_all_restricted_attributes() {
+    let mut resource_timing: ResourceFetchTiming =
+        ResourceFetchTiming::new(ResourceTimingType::Resource);
+    resource_timing.set_attribute(ResourceAttribute::FetchStart);
+    resource_timing.set_attribute(ResourceAttribute::StartTime(ResourceTimeValue::FetchStart));
+    resource_timing.set_attribute(ResourceAttribute::WorkerStart);
+    resource_timing.set_attribute(ResourceAttribute::DomainLookupStart);
+    resource_timing.set_attribute(ResourceAttribute::ConnectStart(1));
+    resource_timing.set_attribute(ResourceAttribute::SecureConnectionStart);
+    resource_timing.set_attribute(ResourceAttribute::ConnectEnd(2));
+    resource_timing.set_attribute(ResourceAttribute::RequestStart);
+    resource_timing.set_attribute(ResourceAttribute::ResponseStart);
+    resource_timing.set_body_sizes(10, 20);
+
+    // verify that a failed TAO check, which happens once the response
+    // arrived, hides everything recorded before it
+    resource_timing.mark_timing_check_failed();
+    resource_timing.set_attribute(ResourceAttribute::ResponseEnd);
+    let restricted = [
+        resource_timing.worker_start,
+        resource_timing.redirect_start,
+        resource_timing.redirect_end,
+        resource_timing.domain_lookup_start,
+        resource_timing.connect_start,
+        resource_timing.connect_end,
+        resource_timing.secure_connection_start,
+        resource_timing.request_start,
+        resource_timing.response_start,
+        resource_timing.transfer_size,
+        resource_timing.encoded_body_size,
+        resource_timing.decoded_body_size,
+    ];
+    assert_eq!(restricted, [0; 12], "restricted attributes should be zero");
+    assert!(resource_timing.start_time > 0, "`start_time` should remain");
+    assert!(
+        resource_timing.fetch_start > 0,
+        "`fetch_start` should remain"
+    );
+    assert!(
+        resource_timing.response_end > 0,
+        "`response_end` should remain"
+    );
+}
+
+#[test]
+fn test_response_status_is_exposed() {
+    let mut resource_timing: ResourceFetchTiming =
+        ResourceFetchTiming::new(ResourceTimingType::Resource);
+    resource_timing.set_response_status(404, false);
+    assert_eq!(resource_timing.response_status, 404);
+
+    // verify that the status does not depend on the timing allow check
+    resource_timing.mark_timing_check_failed();
+    assert_eq!(resource_timing.response_status, 404);
+}
+
+#[test]
+fn test_opaque_response_status_is_zero() {
+    let mut resource_timing: ResourceFetchTiming =
+        ResourceFetchTiming::new(ResourceTimingType::Resource);
+    resource_timing.set_response_status(404, true);
+    assert_eq!(
+        resource_timing.response_status, 0,
+        "an opaque response should not reveal its status"
+    );
+}
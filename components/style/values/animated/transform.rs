@@ -336,7 +336,7 @@ impl Animate for Perspective {
 /// A quaternion used to represent a rotation.
 #[derive(Clone, Copy, Debug)]
 #[cfg_attr(feature = "servo", derive(MallocSizeOf))]
-pub struct Quaternion(f64, f64, f64, f64);
+pub struct Quaternion(pub f64, pub f64, pub f64, pub f64);
 
 impl Quaternion {
     /// Return a quaternion from a unit direction vector and angle (unit: radian).
@@ -549,7 +549,7 @@ impl From<MatrixDecomposed3D> for Matrix3D {
 /// Decompose a 3D matrix.
 /// https://drafts.csswg.org/css-transforms-2/#decomposing-a-3d-matrix
 /// http://www.realtimerendering.com/resources/GraphicsGems/gemsii/unmatrix.c
-fn decompose_3d_matrix(mut matrix: Matrix3D) -> Result<MatrixDecomposed3D, ()> {
+pub fn decompose_3d_matrix(mut matrix: Matrix3D) -> Result<MatrixDecomposed3D, ()> {
     // Combine 2 point.
     let combine = |a: [f32; 3], b: [f32; 3], ascl: f32, bscl: f32| {
         [
@@ -12,6 +12,7 @@ use std::borrow::Cow;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::num::NonZeroU64;
+use std::ops::Range;
 use std::rc::Rc;
 use std::slice;
 use std::sync::{Arc, Mutex};
@@ -185,7 +186,10 @@ pub enum WebGPURequest {
         device_id: id::DeviceId,
         descriptor: Option<TextureViewDescriptor<'static>>,
     },
-    DestroyBuffer(id::BufferId),
+    DestroyBuffer {
+        device_id: id::DeviceId,
+        buffer_id: id::BufferId,
+    },
     DestroyDevice(id::DeviceId),
     DestroySwapChain {
         external_id: u64,
@@ -193,6 +197,13 @@ pub enum WebGPURequest {
     },
     DestroyTexture(id::TextureId),
     Exit(IpcSender<()>),
+    /// Copy `array_buffer` into the mapping of the buffer at `offset`,
+    /// leaving the buffer mapped.
+    FlushMappedRange {
+        buffer_id: id::BufferId,
+        array_buffer: IpcSharedMemory,
+        offset: u64,
+    },
     FreeCommandBuffer(id::CommandBufferId),
     FreeDevice(id::DeviceId),
     RenderBundleEncoderFinish {
@@ -237,6 +248,9 @@ pub enum WebGPURequest {
         is_map_read: bool,
         offset: u64,
         size: u64,
+        /// The ranges of the buffer, within `offset..offset + size`, that were
+        /// handed out by `getMappedRange()` and so may have been written to.
+        write_ranges: Vec<Range<u64>>,
     },
     UpdateWebRenderData {
         buffer_id: id::BufferId,
@@ -791,9 +805,18 @@ impl<'a> WGPU<'a> {
                             self.send_result(device_id, scope_id, result);
                         }
                     },
-                    WebGPURequest::DestroyBuffer(buffer) => {
+                    WebGPURequest::DestroyBuffer {
+                        device_id,
+                        buffer_id,
+                    } => {
                         let global = &self.global;
-                        gfx_select!(buffer => global.buffer_drop(buffer, false));
+                        let result = gfx_select!(buffer_id => global.buffer_destroy(buffer_id));
+                        gfx_select!(buffer_id => global.buffer_drop(buffer_id, false));
+                        // The device may already be gone when the buffer is
+                        // destroyed as part of being garbage collected.
+                        if self.devices.contains_key(&WebGPUDevice(device_id)) {
+                            self.send_result(device_id, scope_id, result);
+                        }
                     },
                     WebGPURequest::DestroyDevice(device) => {
                         let global = &self.global;
@@ -836,6 +859,28 @@ impl<'a> WGPU<'a> {
                         }
                         break;
                     },
+                    WebGPURequest::FlushMappedRange {
+                        buffer_id,
+                        array_buffer,
+                        offset,
+                    } => {
+                        let global = &self.global;
+                        let size = array_buffer.len() as u64;
+                        match gfx_select!(buffer_id =>
+                            global.buffer_get_mapped_range(buffer_id, offset, Some(size)))
+                        {
+                            Ok((slice_pointer, range_size)) => {
+                                let mapping = unsafe {
+                                    slice::from_raw_parts_mut(slice_pointer, range_size as usize)
+                                };
+                                mapping.copy_from_slice(&array_buffer);
+                            },
+                            Err(e) => warn!(
+                                "Failed to flush mapped range of buffer {:?} ({:?})",
+                                buffer_id, e
+                            ),
+                        }
+                    },
                     WebGPURequest::FreeCommandBuffer(command_buffer_id) => {
                         self.error_command_encoders
                             .borrow_mut()
@@ -1156,6 +1201,7 @@ impl<'a> WGPU<'a> {
                         is_map_read,
                         offset,
                         size,
+                        write_ranges,
                     } => {
                         let global = &self.global;
                         if !is_map_read {
@@ -1166,10 +1212,16 @@ impl<'a> WGPU<'a> {
                                     Some(size)
                                 ))
                                 .unwrap();
-                            unsafe {
+                            let mapping = unsafe {
                                 slice::from_raw_parts_mut(slice_pointer, range_size as usize)
+                            };
+                            // Only copy back what script could have written, so the
+                            // gaps between mapped ranges keep their contents.
+                            for range in write_ranges {
+                                let start = (range.start - offset) as usize;
+                                let end = (range.end - offset) as usize;
+                                mapping[start..end].copy_from_slice(&array_buffer[start..end]);
                             }
-                            .copy_from_slice(&array_buffer);
                         }
                         let result = gfx_select!(buffer_id => global.buffer_unmap(buffer_id));
                         self.send_result(device_id, scope_id, result);
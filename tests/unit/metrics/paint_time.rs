@@ -7,6 +7,7 @@ use ipc_channel::ipc;
 use metrics::{PaintTimeMetrics, ProfilerMetadataFactory, ProgressiveWebMetric};
 use msg::constellation_msg::TEST_PIPELINE_ID;
 use profile_traits::time::{ProfilerChan, TimerMetadata};
+use script_traits::{ConstellationControlMsg, ProgressiveWebMetricType};
 use servo_url::ServoUrl;
 
 struct DummyProfilerMetadataFactory {}
@@ -122,3 +123,51 @@ fn test_first_contentful_paint_setter() {
         "first paint is set"
     );
 }
+
+#[test]
+fn test_paint_metrics_are_only_reported_once() {
+    let (sender, _) = ipc::channel().unwrap();
+    let profiler_chan = ProfilerChan(sender);
+    let (layout_sender, _) = ipc::channel().unwrap();
+    let (script_sender, script_receiver) = ipc::channel().unwrap();
+    let mut paint_time_metrics = PaintTimeMetrics::new(
+        TEST_PIPELINE_ID,
+        profiler_chan,
+        layout_sender,
+        script_sender,
+        ServoUrl::parse("about:blank").unwrap(),
+    );
+    paint_time_metrics.set_navigation_start(time::precise_time_ns());
+    let dummy_profiler_metadata_factory = DummyProfilerMetadataFactory {};
+
+    // A non-contentful paint followed by two contentful ones.
+    for (epoch, display_list_is_contentful) in [(0, false), (1, true), (2, true)] {
+        let epoch = Epoch(epoch);
+        paint_time_metrics.maybe_observe_paint_time(
+            &dummy_profiler_metadata_factory,
+            epoch,
+            display_list_is_contentful,
+        );
+        paint_time_metrics.maybe_set_metric(epoch, time::precise_time_ns());
+    }
+
+    let (mut first_paints, mut first_contentful_paints) = (0, 0);
+    while let Ok(msg) = script_receiver.try_recv() {
+        match msg {
+            ConstellationControlMsg::PaintMetric(_, ProgressiveWebMetricType::FirstPaint, _) => {
+                first_paints += 1
+            },
+            ConstellationControlMsg::PaintMetric(
+                _,
+                ProgressiveWebMetricType::FirstContentfulPaint,
+                _,
+            ) => first_contentful_paints += 1,
+            _ => {},
+        }
+    }
+    assert_eq!(first_paints, 1, "first paint is reported once");
+    assert_eq!(
+        first_contentful_paints, 1,
+        "first contentful paint is reported once"
+    );
+}
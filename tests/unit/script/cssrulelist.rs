@@ -0,0 +1,20 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::cssrulelist::{check_rule_limit, Error};
+
+#[test]
+fn rules_can_be_inserted_below_the_limit() {
+    assert!(check_rule_limit(0, 100_000).is_ok());
+    assert!(check_rule_limit(99_999, 100_000).is_ok());
+}
+
+#[test]
+fn inserting_past_the_limit_exceeds_the_quota() {
+    assert!(matches!(
+        check_rule_limit(100_000, 100_000),
+        Err(Error::QuotaExceeded)
+    ));
+    assert!(matches!(check_rule_limit(3, 3), Err(Error::QuotaExceeded)));
+}
@@ -0,0 +1,70 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use devtools_traits::{ScriptToDevtoolsControlMsg, StyleSheetChange, StyleSheetChangeKind};
+use ipc_channel::ipc;
+use msg::constellation_msg::TEST_PIPELINE_ID;
+use script::test::cssstylesheet::{send_style_sheet_change, StyleSheetOwner};
+
+#[test]
+fn setting_an_owner_rule_clears_the_owner_node() {
+    let mut owner = StyleSheetOwner::Node("style");
+    owner.set_rule(Some("@import"));
+    assert_eq!(owner, StyleSheetOwner::Rule("@import"));
+    assert_eq!(owner.node(), None);
+    assert_eq!(owner.rule(), Some(&"@import"));
+}
+
+#[test]
+fn clearing_the_owner_node_leaves_the_owner_rule() {
+    let mut owner: StyleSheetOwner<&str, &str> = StyleSheetOwner::Rule("@import");
+    owner.set_node(None);
+    assert_eq!(owner, StyleSheetOwner::Rule("@import"));
+    owner.set_rule(None);
+    assert_eq!(owner, StyleSheetOwner::Neither);
+}
+
+#[test]
+fn removing_the_owner_element_leaves_neither() {
+    let mut owner: StyleSheetOwner<&str, &str> = StyleSheetOwner::Node("link");
+    owner.set_rule(None);
+    assert_eq!(owner, StyleSheetOwner::Node("link"));
+    owner.set_node(None);
+    assert_eq!(owner, StyleSheetOwner::Neither);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "A stylesheet with an owner rule cannot have an owner node")]
+fn an_imported_stylesheet_cannot_get_an_owner_node() {
+    let mut owner = StyleSheetOwner::Rule("@import");
+    owner.set_node(Some("style"));
+}
+
+#[test]
+fn insert_rule_change_is_sent_to_devtools() {
+    let (chan, port) = ipc::channel().unwrap();
+    send_style_sheet_change(Some(&chan), TEST_PIPELINE_ID, || StyleSheetChange {
+        owner_node: Some("style-1".to_owned()),
+        href: None,
+        kind: StyleSheetChangeKind::RuleInserted(1),
+    });
+    match port.try_recv().expect("a devtools message") {
+        ScriptToDevtoolsControlMsg::StyleSheetChanged(pipeline_id, change) => {
+            assert_eq!(pipeline_id, TEST_PIPELINE_ID);
+            assert_eq!(change.owner_node.as_deref(), Some("style-1"));
+            assert_eq!(change.href, None);
+            assert!(matches!(change.kind, StyleSheetChangeKind::RuleInserted(1)));
+        },
+        msg => panic!("unexpected devtools message {:?}", msg),
+    }
+    assert!(port.try_recv().is_err());
+}
+
+#[test]
+fn no_change_is_built_without_devtools() {
+    send_style_sheet_change(None, TEST_PIPELINE_ID, || {
+        panic!("the change is only described for devtools")
+    });
+}
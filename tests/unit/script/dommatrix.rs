@@ -0,0 +1,1312 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+
+use euclid::default::{Point3D, Transform2D, Transform3D};
+use euclid::Angle;
+use script::test::dommatrix::{
+    angle_argument_in_degrees, cached_rotation_quaternion, clone_matrix, component_keeps_2d,
+    compose_matrix, decompose_matrix, dommatrix2dinit_to_matrix, dommatrixinit_to_matrix,
+    entries_to_matrix, invert_matrix, matrix_2d_components, matrix_column,
+    matrix_entries_from_bytes, matrix_equals, matrix_from_row_major, matrix_from_rows, matrix_hash,
+    matrix_row, matrix_to_row_major, multiply_all, parse_angle_in_degrees, post_multiply,
+    pre_multiply, quaternion_to_matrix, serialize_matrix, set_matrix_component, then_2d,
+    transform_homogeneous_point, transform_points, transform_to_matrix,
+    transform_to_matrix_with_origin, translated_matrix, validate_scale_origin, DOMMatrix2DInit,
+    DOMMatrixBufferFormat, DOMMatrixInit, DOMMatrixReadOnly, DOMMatrixReadOnlyMethods, Error,
+    MatrixOp, TransformCache,
+};
+
+#[test]
+fn column_3_is_translation() {
+    let matrix = Transform3D::translation(10., 20., 30.);
+    assert_eq!(matrix_column(&matrix, 3), Some([10., 20., 30., 1.]));
+}
+
+#[test]
+fn row_3_of_translation() {
+    let matrix = Transform3D::translation(10., 20., 30.);
+    assert_eq!(matrix_row(&matrix, 3), Some([0., 0., 0., 1.]));
+    assert_eq!(matrix_row(&matrix, 0), Some([1., 0., 0., 10.]));
+}
+
+#[test]
+fn out_of_bounds_index() {
+    let matrix = Transform3D::identity();
+    assert_eq!(matrix_column(&matrix, 4), None);
+    assert_eq!(matrix_row(&matrix, 4), None);
+}
+
+#[test]
+fn compose_matches_chained_calls() {
+    let start = Transform3D::translation(1., 2., 3.);
+    let composed = compose_matrix(
+        &start,
+        &[
+            MatrixOp::Translate(10., 20., 30.),
+            MatrixOp::Scale(2., 3., 4.),
+            MatrixOp::Rotate(0., 0., 90.),
+        ],
+    );
+
+    // What `start.translate(10, 20, 30).scale(2, 3, 4).rotate(90)` computes.
+    let mut chained = Transform3D::translation(10., 20., 30.).then(&start);
+    chained = Transform3D::scale(2., 3., 4.).then(&chained);
+    chained = Transform3D::rotation(0., 0., 1., Angle::radians(90f64.to_radians())).then(&chained);
+
+    assert_eq!(composed, chained);
+}
+
+#[test]
+fn compose_applies_last_op_first_to_points() {
+    let composed = compose_matrix(
+        &Transform3D::identity(),
+        &[
+            MatrixOp::Translate(10., 0., 0.),
+            MatrixOp::Scale(2., 2., 1.),
+        ],
+    );
+    let point = composed
+        .transform_point3d(Point3D::new(1., 1., 0.))
+        .unwrap();
+    assert_eq!(point, Point3D::new(12., 2., 0.));
+}
+
+#[test]
+fn components_of_2d_matrix() {
+    let matrix = Transform3D::new_2d(1., 2., 3., 4., 5., 6.);
+    assert_eq!(
+        matrix_2d_components(&matrix, true).ok(),
+        Some([1., 2., 3., 4., 5., 6.])
+    );
+}
+
+#[test]
+fn components_of_3d_matrix() {
+    let matrix = Transform3D::translation(1., 2., 3.);
+    assert!(matches!(
+        matrix_2d_components(&matrix, false),
+        Err(Error::InvalidState)
+    ));
+}
+
+#[test]
+fn angle_strings_in_degrees() {
+    assert_eq!(parse_angle_in_degrees("0.25turn"), Some(90.));
+    assert_eq!(parse_angle_in_degrees("0.5turn"), Some(180.));
+    assert_eq!(parse_angle_in_degrees("45deg"), Some(45.));
+    assert_eq!(parse_angle_in_degrees("45"), None);
+}
+
+#[test]
+fn angle_arguments_must_be_css_angles() {
+    assert!(matches!(angle_argument_in_degrees("0.25turn"), Ok(d) if d == 90.));
+    // Numbers, however written, are left to the standard overloads and their
+    // ToNumber conversion.
+    for not_an_angle in ["45", " 45 ", "0x10", "null", ""] {
+        assert!(matches!(
+            angle_argument_in_degrees(not_an_angle),
+            Err(Error::Syntax)
+        ));
+    }
+}
+
+#[test]
+fn rotate_by_turn_string_matches_degrees() {
+    let turn = parse_angle_in_degrees("0.25turn").unwrap();
+    let identity = Transform3D::identity();
+    assert_eq!(
+        compose_matrix(&identity, &[MatrixOp::Rotate(0., 0., turn)]),
+        compose_matrix(&identity, &[MatrixOp::Rotate(0., 0., 90.)])
+    );
+}
+
+#[test]
+fn z_axis_rotations_agree() {
+    let identity = Transform3D::identity();
+    let rotate = MatrixOp::Rotate(0., 0., 45.);
+    let rotate_axis_angle = MatrixOp::RotateAxisAngle(0., 0., 1., 45.);
+    assert_eq!(
+        compose_matrix(&identity, &[rotate]),
+        compose_matrix(&identity, &[rotate_axis_angle])
+    );
+    assert!(rotate.preserves_2d());
+    assert!(rotate_axis_angle.preserves_2d());
+    assert!(!MatrixOp::RotateAxisAngle(1., 0., 0., 45.).preserves_2d());
+}
+
+#[test]
+fn then_2d_matches_full_multiply() {
+    let first = Transform3D::new_2d(1., 2., 3., 4., 5., 6.);
+    let second = Transform3D::new_2d(-2., 0.5, 1.5, 3., -7., 8.);
+    assert_eq!(then_2d(&first, &second), first.then(&second));
+    assert_eq!(then_2d(&second, &first), second.then(&first));
+}
+
+/// Whether `a` and `b` have the same components, NaNs included.
+fn same_components(a: &Transform3D<f64>, b: &Transform3D<f64>) -> bool {
+    a.to_array()
+        .iter()
+        .zip(b.to_array().iter())
+        .all(|(a, b)| a == b || (a.is_nan() && b.is_nan()))
+}
+
+#[test]
+fn then_2d_of_an_infinite_matrix_matches_full_multiply() {
+    let infinite = Transform3D::new_2d(f64::INFINITY, 0., 0., 1., 5., 6.);
+    let other = Transform3D::new_2d(2., 1., 0.5, 3., -1., 4.);
+    assert!(same_components(
+        &then_2d(&infinite, &other),
+        &infinite.then(&other)
+    ));
+    assert!(same_components(
+        &then_2d(&other, &infinite),
+        &other.then(&infinite)
+    ));
+    // The m13 the 2D multiply would leave at 0 is Infinity * 0.
+    assert!(then_2d(&infinite, &other).m13.is_nan());
+    // Through multiplySelf(), including by the identity.
+    let identity = Transform3D::identity();
+    assert!(same_components(
+        &post_multiply(&infinite, true, &identity, true),
+        &identity.then(&infinite)
+    ));
+}
+
+#[test]
+fn set_component_by_index() {
+    let mut matrix = Transform3D::identity();
+    assert!(set_matrix_component(&mut matrix, 1, 1, 2.).is_ok());
+    assert!(set_matrix_component(&mut matrix, 4, 2, 7.).is_ok());
+    assert_eq!(matrix.m11, 2.);
+    assert_eq!(matrix.m42, 7.);
+    assert!(set_matrix_component(&mut matrix, 0, 1, 1.).is_err());
+    assert!(set_matrix_component(&mut matrix, 4, 5, 1.).is_err());
+}
+
+#[test]
+fn set_component_reads_back_through_the_named_getters() {
+    let matrix = DOMMatrixReadOnly::new_inherited(true, Transform3D::identity());
+    assert!(matrix.set_component(1, 1, 2.).is_ok());
+    assert!(matrix.set_component(4, 2, 7.).is_ok());
+    assert_eq!(matrix.M11(), 2.);
+    assert_eq!(matrix.M42(), 7.);
+    assert!(matrix.is2D());
+    assert!(matrix.take_dirty());
+}
+
+#[test]
+fn out_of_range_set_component_is_not_a_mutation() {
+    let matrix = DOMMatrixReadOnly::new_inherited(true, Transform3D::identity());
+    for (row, col) in [(0, 1), (4, 5), (5, 4)] {
+        assert!(matches!(
+            matrix.set_component(row, col, 1.),
+            Err(Error::IndexSize)
+        ));
+    }
+    assert!(!matrix.take_dirty());
+    assert!(matrix.is2D());
+    assert_eq!(*matrix.matrix(), Transform3D::identity());
+}
+
+#[test]
+fn set_component_of_a_3d_slot_makes_the_matrix_3d() {
+    for (row, col) in [(1, 3), (4, 3), (3, 4)] {
+        let matrix = DOMMatrixReadOnly::new_inherited(true, Transform3D::identity());
+        assert!(matrix.set_component(row, col, 5.).is_ok());
+        assert!(!matrix.is2D());
+    }
+    // The same slots keep the matrix 2D at their identity value.
+    assert!(component_keeps_2d(1, 3, -0.));
+    assert!(component_keeps_2d(3, 3, 1.));
+    assert!(!component_keeps_2d(4, 4, 0.));
+    assert!(component_keeps_2d(4, 1, f64::NAN));
+}
+
+#[test]
+fn serialize_2d_and_3d_matrices() {
+    let matrix = Transform3D::new_2d(1., 0.5, -0., 1e21, 1e-7, 100.);
+    assert_eq!(
+        serialize_matrix(&matrix, true).ok(),
+        Some("matrix(1, 0.5, 0, 1e+21, 1e-7, 100)".to_owned())
+    );
+    let matrix = Transform3D::translation(1.25, 2., 3.);
+    assert_eq!(
+        serialize_matrix(&matrix, false).ok(),
+        Some("matrix3d(1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 1.25, 2, 3, 1)".to_owned())
+    );
+}
+
+#[test]
+fn serialize_non_finite_matrix() {
+    let matrix = Transform3D::translation(f64::INFINITY, 0., 0.);
+    assert!(matches!(
+        serialize_matrix(&matrix, true),
+        Err(Error::InvalidState)
+    ));
+}
+
+#[test]
+fn stringifying_a_non_finite_matrix_throws() {
+    let matrix = DOMMatrixReadOnly::new_inherited(true, Transform3D::identity());
+    assert!(matrix.ensure_finite().is_ok());
+    matrix.set_m42(f64::NAN);
+    assert!(matches!(matrix.ensure_finite(), Err(Error::InvalidState)));
+    assert!(matches!(matrix.Stringifier(), Err(Error::InvalidState)));
+    assert!(matches!(
+        matrix.ToMatrix3dString(),
+        Err(Error::InvalidState)
+    ));
+}
+
+fn assert_close(actual: &[f64], expected: &[f64]) {
+    for (a, e) in actual.iter().zip(expected) {
+        assert!((a - e).abs() < 1e-4, "{:?} != {:?}", actual, expected);
+    }
+}
+
+#[test]
+fn decompose_translate_scale_rotate() {
+    let matrix = Transform3D::scale(2., 3., 4.)
+        .then(&Transform3D::rotation(0., 0., 1., Angle::degrees(90.)))
+        .then(&Transform3D::translation(10., 20., 30.));
+    let decomposed = decompose_matrix(&matrix).expect("matrix should be decomposable");
+
+    assert_close(&decomposed.translate, &[10., 20., 30.]);
+    assert_close(&decomposed.scale, &[2., 3., 4.]);
+    let half_sqrt_2 = std::f64::consts::FRAC_1_SQRT_2;
+    assert_close(&decomposed.quaternion, &[0., 0., half_sqrt_2, half_sqrt_2]);
+}
+
+#[test]
+fn decompose_singular_matrix() {
+    let matrix = Transform3D::scale(0., 1., 1.);
+    assert!(decompose_matrix(&matrix).is_none());
+}
+
+#[test]
+fn equal_matrices_hash_equally() {
+    let first = Transform3D::translation(1., 2., 3.);
+    let second = Transform3D::translation(1., 2., 3.);
+    assert_eq!(matrix_hash(&first), matrix_hash(&second));
+
+    let mut negative_zero = Transform3D::identity();
+    negative_zero.m41 = -0.;
+    assert_eq!(
+        matrix_hash(&negative_zero),
+        matrix_hash(&Transform3D::identity())
+    );
+}
+
+#[test]
+fn different_matrices_hash_differently() {
+    let first = Transform3D::translation(1., 2., 3.);
+    let second = Transform3D::translation(1., 2., 4.);
+    assert_ne!(matrix_hash(&first), matrix_hash(&second));
+}
+
+#[test]
+fn init_with_explicit_is_2d_is_2d() {
+    let mut init = DOMMatrixInit::empty();
+    init.parent.m11 = Some(2.);
+    init.is2D = Some(true);
+    let (is_2d, matrix) = dommatrixinit_to_matrix(&init).expect("init should be valid");
+    assert!(is_2d);
+    assert_eq!(matrix.m11, 2.);
+}
+
+#[test]
+fn init_with_explicit_is_2d_rejects_3d_components() {
+    let mut init = DOMMatrixInit::empty();
+    init.m13 = 1.;
+    init.is2D = Some(true);
+    assert!(matches!(
+        dommatrixinit_to_matrix(&init),
+        Err(Error::Type(_))
+    ));
+}
+
+#[test]
+fn init_with_explicit_is_2d_false_is_3d() {
+    let mut init = DOMMatrixInit::empty();
+    init.is2D = Some(false);
+    let (is_2d, _) = dommatrixinit_to_matrix(&init).expect("init should be valid");
+    assert!(!is_2d);
+}
+
+#[test]
+fn rotate_with_one_argument_is_a_z_rotation() {
+    let rotation = MatrixOp::rotate(45., None, None);
+    assert!(rotation.preserves_2d());
+    assert_eq!(
+        compose_matrix(&Transform3D::identity(), &[rotation]),
+        compose_matrix(&Transform3D::identity(), &[MatrixOp::Rotate(0., 0., 45.)])
+    );
+}
+
+#[test]
+fn rotate_about_y_is_3d() {
+    assert!(!MatrixOp::rotate(0., Some(45.), None).preserves_2d());
+}
+
+#[test]
+fn rotate_about_z_with_three_arguments_is_2d() {
+    assert!(MatrixOp::rotate(0., Some(0.), Some(45.)).preserves_2d());
+}
+
+#[test]
+fn rotate_about_x_is_3d() {
+    assert!(!MatrixOp::rotate(45., Some(0.), Some(0.)).preserves_2d());
+}
+
+#[test]
+fn init_2d_fills_in_aliases() {
+    let mut init = DOMMatrix2DInit::empty();
+    init.m11 = Some(2.);
+    init.e = Some(5.);
+    assert_eq!(
+        dommatrix2dinit_to_matrix(&init).expect("init should be valid"),
+        Transform2D::new(2., 0., 0., 1., 5., 0.)
+    );
+}
+
+#[test]
+fn init_2d_rejects_conflicting_aliases() {
+    let mut init = DOMMatrix2DInit::empty();
+    init.a = Some(1.);
+    init.m11 = Some(2.);
+    assert!(matches!(
+        dommatrix2dinit_to_matrix(&init),
+        Err(Error::Type(_))
+    ));
+}
+
+#[test]
+fn row_major_is_the_transpose_of_to_array() {
+    let matrix = Transform3D::new(
+        1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 16.,
+    );
+    let column_major = matrix.to_array();
+    let row_major = matrix_to_row_major(&matrix);
+    for row in 0..4 {
+        for column in 0..4 {
+            assert_eq!(row_major[row * 4 + column], column_major[column * 4 + row]);
+        }
+    }
+    assert_eq!(matrix_from_row_major(&row_major), matrix);
+}
+
+#[test]
+fn entries_from_buffer_with_byte_offset() {
+    let entries: Vec<f32> = (1..=16).map(|i| i as f32).collect();
+    let mut bytes = vec![0xff; 8];
+    for entry in &entries {
+        bytes.extend_from_slice(&entry.to_ne_bytes());
+    }
+    let read = matrix_entries_from_bytes(&bytes, 8, DOMMatrixBufferFormat::Float32)
+        .expect("buffer should be valid");
+    let expected: Vec<f64> = entries.iter().map(|&entry| entry as f64).collect();
+    assert_eq!(read, expected);
+}
+
+#[test]
+fn entries_from_buffer_reject_bad_offsets() {
+    let bytes = vec![0; 48];
+    assert!(matches!(
+        matrix_entries_from_bytes(&bytes, 4, DOMMatrixBufferFormat::Float64),
+        Err(Error::Range(_))
+    ));
+    assert!(matches!(
+        matrix_entries_from_bytes(&bytes, 56, DOMMatrixBufferFormat::Float64),
+        Err(Error::Range(_))
+    ));
+    assert!(matches!(
+        matrix_entries_from_bytes(&bytes[..46], 0, DOMMatrixBufferFormat::Float32),
+        Err(Error::Range(_))
+    ));
+}
+
+#[test]
+fn multiply_all_matches_chained_multiply() {
+    let mut translate = DOMMatrixInit::empty();
+    translate.parent.m41 = Some(10.);
+    translate.parent.m42 = Some(-5.);
+    let mut scale = DOMMatrixInit::empty();
+    scale.parent.m11 = Some(2.);
+    scale.parent.m22 = Some(3.);
+    let mut perspective = DOMMatrixInit::empty();
+    perspective.m34 = -0.01;
+    let matrices = [translate, scale, perspective];
+
+    let start = Transform3D::rotation(0., 0., 1., Angle::degrees(30.));
+    let chained = matrices.iter().fold(start, |matrix, init| {
+        let (_, other) = dommatrixinit_to_matrix(init).expect("init should be valid");
+        other.then(&matrix)
+    });
+    assert_eq!(
+        multiply_all(true, &start, &matrices).expect("inits should be valid"),
+        (false, chained)
+    );
+    assert_eq!(
+        multiply_all(true, &start, &matrices[..2]).expect("inits should be valid"),
+        (
+            true,
+            matrices[..2].iter().fold(start, |matrix, init| {
+                let (_, other) = dommatrixinit_to_matrix(init).expect("init should be valid");
+                then_2d(&other, &matrix)
+            })
+        )
+    );
+    assert_eq!(
+        multiply_all(true, &start, &[]).expect("no inits is valid"),
+        (true, start)
+    );
+}
+
+/// Serialize `matrix` and parse it back as the string constructor would.
+/// Transform functions are parsed with single precision, so components only
+/// survive to within that.
+fn assert_round_trips(matrix: Transform3D<f64>, is_2d: bool) {
+    let serialized = serialize_matrix(&matrix, is_2d).expect("matrix should be finite");
+    let (parsed_is_2d, parsed) =
+        transform_to_matrix(serialized.clone()).expect("serialization should parse");
+    assert_eq!(parsed_is_2d, is_2d, "{}", serialized);
+    for (a, e) in parsed.to_array().iter().zip(matrix.to_array().iter()) {
+        assert!(
+            (a - e).abs() <= e.abs() * 1e-6 + 1e-9,
+            "{} parsed as {:?}",
+            serialized,
+            parsed
+        );
+    }
+}
+
+#[test]
+fn stringifier_round_trips_2d_matrix() {
+    let rotation = Transform3D::rotation(0., 0., 1., Angle::degrees(30.));
+    assert_round_trips(
+        Transform3D::translation(12.5, -3., 0.).then(&rotation),
+        true,
+    );
+    // Numbers that serialize in exponential notation parse back too.
+    assert_round_trips(
+        Transform3D::new(
+            1., 0.5, 0., 0., 0., 1e21, 0., 0., 0., 0., 1., 0., 1e-7, 100., 0., 1.,
+        ),
+        true,
+    );
+}
+
+#[test]
+fn stringifier_round_trips_perspective_matrix() {
+    let mut matrix = Transform3D::translation(0., 0., -50.);
+    matrix.m34 = -1. / 300.;
+    assert_round_trips(matrix, false);
+}
+
+#[test]
+fn skew_x_of_2d_matrix_is_2d() {
+    let skew = MatrixOp::SkewX(30.);
+    assert!(skew.preserves_2d());
+    let matrix = compose_matrix(&Transform3D::translation(5., 10., 0.), &[skew]);
+    assert!(matrix.is_2d());
+    assert_eq!(
+        matrix,
+        Transform3D::skew(Angle::degrees(30.), Angle::radians(0.))
+            .then(&Transform3D::translation(5., 10., 0.))
+    );
+}
+
+#[test]
+fn skew_x_after_3d_operation_is_3d() {
+    let ops = [MatrixOp::Translate(0., 0., 5.), MatrixOp::SkewX(30.)];
+    // The DOM object's is2D flag is cleared by any op that doesn't preserve 2D.
+    assert!(!ops.iter().all(MatrixOp::preserves_2d));
+    assert!(!compose_matrix(&Transform3D::identity(), &ops).is_2d());
+}
+
+#[test]
+fn transform_about_origin_matches_manual_composition() {
+    let (is_2d, matrix) = transform_to_matrix_with_origin("rotate(90deg)".to_owned(), 50., 50.)
+        .expect("transform should parse");
+    assert!(is_2d);
+    let manual = compose_matrix(
+        &Transform3D::identity(),
+        &[
+            MatrixOp::Translate(50., 50., 0.),
+            MatrixOp::Rotate(0., 0., 90.),
+            MatrixOp::Translate(-50., -50., 0.),
+        ],
+    );
+    assert_close(&matrix.to_array(), &manual.to_array());
+    // The origin itself stays put.
+    let origin = matrix
+        .transform_point3d(Point3D::new(50., 50., 0.))
+        .expect("matrix should be invertible");
+    assert_close(&[origin.x, origin.y], &[50., 50.]);
+}
+
+#[test]
+fn multiplying_by_identity_leaves_matrix_unchanged() {
+    // A full multiply would turn the -0 components into +0.
+    let mut matrix = Transform3D::translation(-0., 3.5, -0.);
+    matrix.m12 = -0.;
+    let bits = |matrix: &Transform3D<f64>| matrix.to_array().map(f64::to_bits);
+    let identity = Transform3D::identity();
+    for is_2d in [true, false] {
+        assert_eq!(
+            bits(&post_multiply(&matrix, is_2d, &identity, is_2d)),
+            bits(&matrix)
+        );
+        assert_eq!(
+            bits(&pre_multiply(&matrix, is_2d, &identity, is_2d)),
+            bits(&matrix)
+        );
+    }
+}
+
+#[test]
+fn multiplying_a_non_finite_matrix_by_identity_is_a_full_multiply() {
+    let identity = Transform3D::identity();
+    let mut matrix = Transform3D::translation(f64::INFINITY, 2., 0.);
+    matrix.m22 = f64::NAN;
+    let multiplied = post_multiply(&matrix, false, &identity, false);
+    assert!(same_components(&multiplied, &identity.then(&matrix)));
+    // Infinity * 0 is NaN, all along the infinite component's column.
+    assert!(multiplied.m11.is_nan());
+    let multiplied = pre_multiply(&matrix, false, &identity, false);
+    assert!(same_components(&multiplied, &matrix.then(&identity)));
+    // And its row here.
+    assert!(multiplied.m44.is_nan());
+}
+
+#[test]
+fn pre_and_post_multiply_order() {
+    let translation = Transform3D::translation(10., 0., 0.);
+    let scale = Transform3D::scale(2., 2., 1.);
+    // translation · scale scales first, then translates.
+    assert_eq!(
+        post_multiply(&translation, true, &scale, true),
+        scale.then(&translation)
+    );
+    assert_eq!(
+        pre_multiply(&translation, true, &scale, true),
+        translation.then(&scale)
+    );
+}
+
+#[test]
+fn flip_x_of_translated_2d_matrix() {
+    let flip = MatrixOp::FlipX;
+    assert!(flip.preserves_2d());
+    let matrix = compose_matrix(&Transform3D::translation(10., 20., 0.), &[flip]);
+    assert!(matrix.is_2d());
+    // Post-multiplying negates the first column, a and b, and applies the
+    // flip before the translation.
+    assert_eq!(
+        matrix_2d_components(&matrix, true).ok(),
+        Some([-1., 0., 0., 1., 10., 20.])
+    );
+    let point = matrix
+        .transform_point3d(Point3D::new(1., 1., 0.))
+        .expect("matrix should be invertible");
+    assert_eq!(point, Point3D::new(9., 21., 0.));
+}
+
+#[test]
+fn flip_y_of_skewed_2d_matrix() {
+    let skewed = Transform3D::new_2d(1., 0.5, 2., 1., 10., 20.);
+    let matrix = compose_matrix(&skewed, &[MatrixOp::FlipY]);
+    // The second column, c and d, is negated.
+    assert_eq!(
+        matrix_2d_components(&matrix, true).ok(),
+        Some([1., 0.5, -2., -1., 10., 20.])
+    );
+}
+
+#[test]
+fn translate_without_tz_stays_2d() {
+    let translate = MatrixOp::Translate(10., 20., 0.);
+    assert!(translate.preserves_2d());
+    let matrix = compose_matrix(&Transform3D::identity(), &[translate]);
+    assert_eq!((matrix.m41, matrix.m42, matrix.m43), (10., 20., 0.));
+}
+
+#[test]
+fn translate_with_tz_is_3d() {
+    let translate = MatrixOp::Translate(10., 20., 5.);
+    assert!(!translate.preserves_2d());
+    let matrix = compose_matrix(&Transform3D::identity(), &[translate]);
+    assert_eq!((matrix.m41, matrix.m42, matrix.m43), (10., 20., 5.));
+}
+
+#[test]
+fn repeated_transform_strings_are_parsed_once() {
+    let parses = Cell::new(0);
+    let parse = |value: &str| {
+        parses.set(parses.get() + 1);
+        transform_to_matrix(value.to_owned())
+    };
+    let mut cache = TransformCache::new(2);
+    let first = cache.get_or_parse("translate(10px, 20px)", parse);
+    let second = cache.get_or_parse("translate(10px, 20px)", parse);
+    assert_eq!(parses.get(), 1);
+    assert_eq!(first.ok(), second.ok());
+}
+
+#[test]
+fn transform_cache_evicts_least_recently_used_string() {
+    let parses = Cell::new(0);
+    let parse = |value: &str| {
+        parses.set(parses.get() + 1);
+        transform_to_matrix(value.to_owned())
+    };
+    let mut cache = TransformCache::new(2);
+    let _ = cache.get_or_parse("scale(2)", parse);
+    let _ = cache.get_or_parse("rotate(45deg)", parse);
+    // Using scale(2) again makes rotate(45deg) the one to evict.
+    let _ = cache.get_or_parse("scale(2)", parse);
+    let _ = cache.get_or_parse("skewX(10deg)", parse);
+    assert_eq!(parses.get(), 3);
+    let _ = cache.get_or_parse("scale(2)", parse);
+    assert_eq!(parses.get(), 3);
+    let _ = cache.get_or_parse("rotate(45deg)", parse);
+    assert_eq!(parses.get(), 4);
+}
+
+#[test]
+fn invalid_transform_strings_are_not_cached() {
+    let parses = Cell::new(0);
+    let parse = |value: &str| {
+        parses.set(parses.get() + 1);
+        transform_to_matrix(value.to_owned())
+    };
+    let mut cache = TransformCache::new(2);
+    assert!(cache.get_or_parse("not a transform", parse).is_err());
+    assert!(cache.get_or_parse("not a transform", parse).is_err());
+    assert_eq!(parses.get(), 2);
+}
+
+#[test]
+fn exactly_equal_matrices() {
+    let mut init = DOMMatrixInit::empty();
+    init.parent.m41 = Some(10.);
+    init.parent.m42 = Some(-5.);
+    let matrix = Transform3D::translation(10., -5., 0.);
+    assert_eq!(matrix_equals(true, &matrix, &init).ok(), Some(true));
+    // Same components, but one of them is 3D.
+    assert_eq!(matrix_equals(false, &matrix, &init).ok(), Some(false));
+    let moved = Transform3D::translation(10., -5.000001, 0.);
+    assert_eq!(matrix_equals(true, &moved, &init).ok(), Some(false));
+}
+
+#[test]
+fn negative_zero_equals_zero() {
+    let mut init = DOMMatrixInit::empty();
+    init.parent.m41 = Some(-0.);
+    assert_eq!(
+        matrix_equals(true, &Transform3D::identity(), &init).ok(),
+        Some(true)
+    );
+}
+
+#[test]
+fn nan_component_is_never_equal() {
+    let mut init = DOMMatrixInit::empty();
+    init.parent.m41 = Some(f64::NAN);
+    let mut matrix = Transform3D::identity();
+    matrix.m41 = f64::NAN;
+    assert_eq!(matrix_equals(true, &matrix, &init).ok(), Some(false));
+}
+
+#[test]
+fn scale_2d_about_an_origin_stays_2d() {
+    let ops = MatrixOp::scale_2d(2., 3., 50., 20.);
+    assert!(ops.iter().all(MatrixOp::preserves_2d));
+    let matrix = compose_matrix(&Transform3D::identity(), &ops);
+    assert!(matrix.is_2d());
+    assert_eq!(
+        matrix_2d_components(&matrix, true).ok(),
+        Some([2., 0., 0., 3., -50., -40.])
+    );
+    // The origin itself stays put.
+    let origin = matrix
+        .transform_point3d(Point3D::new(50., 20., 0.))
+        .expect("matrix should be invertible");
+    assert_eq!(origin, Point3D::new(50., 20., 0.));
+}
+
+#[test]
+fn multiplying_by_an_empty_init_is_the_identity() {
+    let (other_is_2d, other) =
+        dommatrixinit_to_matrix(&DOMMatrixInit::empty()).expect("empty init is valid");
+    assert!(other_is_2d);
+    assert_eq!(other, Transform3D::identity());
+
+    let matrix = Transform3D::new_2d(1., 0.5, 2., 1., 10., 20.);
+    assert_eq!(post_multiply(&matrix, true, &other, other_is_2d), matrix);
+    // multiply() only clears is2D when the other matrix is 3D.
+    assert_eq!(
+        multiply_all(true, &matrix, &[DOMMatrixInit::empty()]).ok(),
+        Some((true, matrix))
+    );
+}
+
+#[test]
+fn four_quarter_turns_are_exactly_the_identity() {
+    let identity = Transform3D::identity();
+    let quarter_turn = compose_matrix(&identity, &[MatrixOp::Rotate(0., 0., 90.)]);
+    assert_eq!(quarter_turn.m11, 0.);
+    assert_eq!(quarter_turn.m22, 0.);
+    let ops = [MatrixOp::Rotate(0., 0., 90.); 4];
+    assert_eq!(compose_matrix(&identity, &ops), identity);
+    let ops = [MatrixOp::RotateAxisAngle(1., 0., 0., 90.); 4];
+    assert_eq!(compose_matrix(&identity, &ops), identity);
+}
+
+#[test]
+fn quarter_turns_match_the_general_rotation() {
+    let identity = Transform3D::identity();
+    for degrees in [-270., -90., 90., 180., 270., 450.] {
+        for (x, y, z) in [(1., 0., 0.), (0., 1., 0.), (0., 0., 1.)] {
+            let exact = compose_matrix(&identity, &[MatrixOp::RotateAxisAngle(x, y, z, degrees)]);
+            let general = Transform3D::rotation(x, y, z, Angle::degrees(degrees));
+            assert_close(&exact.to_array(), &general.to_array());
+        }
+    }
+}
+
+#[test]
+fn transform_point_with_and_without_perspective_divide() {
+    // perspective(100px): w = 1 - z / 100.
+    let mut matrix = Transform3D::identity();
+    matrix.m34 = -0.01;
+    let point = [10., 20., 50., 1.];
+    assert_eq!(
+        transform_homogeneous_point(&matrix, point, false),
+        [10., 20., 50., 0.5]
+    );
+    assert_eq!(
+        transform_homogeneous_point(&matrix, point, true),
+        [20., 40., 100., 1.]
+    );
+    // A point at infinity is left alone rather than divided by 0.
+    assert_eq!(
+        transform_homogeneous_point(&matrix, [10., 20., 100., 1.], true),
+        [10., 20., 100., 0.]
+    );
+}
+
+#[test]
+fn matrix_from_4x4_rows() {
+    let rows = vec![
+        vec![1., 2., 3., 4.],
+        vec![5., 6., 7., 8.],
+        vec![9., 10., 11., 12.],
+        vec![13., 14., 15., 16.],
+    ];
+    let (is_2d, matrix) = matrix_from_rows(&rows).expect("rows should be valid");
+    assert!(!is_2d);
+    // The first row holds m11, m21, m31 and m41.
+    assert_eq!(
+        (matrix.m11, matrix.m21, matrix.m31, matrix.m41),
+        (1., 2., 3., 4.)
+    );
+    assert_eq!(
+        (matrix.m14, matrix.m24, matrix.m34, matrix.m44),
+        (13., 14., 15., 16.)
+    );
+    let flattened: Vec<f64> = rows.into_iter().flatten().collect();
+    assert_eq!(matrix_to_row_major(&matrix)[..], flattened[..]);
+}
+
+#[test]
+fn matrix_from_2x3_rows() {
+    let rows = vec![vec![1., 3., 5.], vec![2., 4., 6.]];
+    let (is_2d, matrix) = matrix_from_rows(&rows).expect("rows should be valid");
+    assert!(is_2d);
+    assert_eq!(
+        matrix_2d_components(&matrix, is_2d).ok(),
+        Some([1., 2., 3., 4., 5., 6.])
+    );
+}
+
+#[test]
+fn matrix_from_rows_of_the_wrong_shape() {
+    assert!(matches!(
+        matrix_from_rows(&[vec![1., 0.], vec![0., 1.]]),
+        Err(Error::Type(_))
+    ));
+    assert!(matches!(
+        matrix_from_rows(&[vec![1., 0., 0., 0.], vec![0., 1., 0., 0.]]),
+        Err(Error::Type(_))
+    ));
+}
+
+#[test]
+fn serialization_follows_the_is_2d_flag_not_the_values() {
+    let matrix =
+        DOMMatrixReadOnly::new_inherited(false, Transform3D::new_2d(1., 0., 0., 1., 10., 20.));
+    assert!(matrix.matrix().is_2d());
+    assert_eq!(
+        matrix.Stringifier().ok().map(String::from),
+        Some("matrix3d(1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 10, 20, 0, 1)".to_owned())
+    );
+
+    matrix.recompute_is2d();
+    assert!(matrix.is2D());
+    assert_eq!(
+        matrix.Stringifier().ok().map(String::from),
+        Some("matrix(1, 0, 0, 1, 10, 20)".to_owned())
+    );
+}
+
+#[test]
+fn quaternion_matches_axis_angle_rotation() {
+    // 60 degrees about (1, 2, 3): the quaternion is the normalized axis
+    // scaled by sin(30°), with w = cos(30°).
+    let length = 14f64.sqrt();
+    let half = 30f64.to_radians();
+    let (s, c) = half.sin_cos();
+    let from_quaternion = quaternion_to_matrix(s / length, 2. * s / length, 3. * s / length, c);
+    let from_axis_angle = compose_matrix(
+        &Transform3D::identity(),
+        &[MatrixOp::RotateAxisAngle(1., 2., 3., 60.)],
+    );
+    assert_close(&from_quaternion.to_array(), &from_axis_angle.to_array());
+}
+
+#[test]
+fn quaternion_is_normalized() {
+    assert_close(
+        &quaternion_to_matrix(0., 0., 2., 2.).to_array(),
+        &quaternion_to_matrix(0., 0., 0.5, 0.5).to_array(),
+    );
+    assert_eq!(
+        quaternion_to_matrix(0., 0., 0., 0.),
+        Transform3D::identity()
+    );
+}
+
+// The origin of scale(), scaleSelf(), scale3d() and scale3dSelf().
+#[test]
+fn nan_scale_origin_is_rejected_when_strict() {
+    for origin in [[f64::NAN, 0., 0.], [0., f64::NAN, 0.], [0., 0., f64::NAN]] {
+        assert!(matches!(
+            validate_scale_origin(origin, true),
+            Err(Error::Type(_))
+        ));
+    }
+    assert!(validate_scale_origin([1., 2., 3.], true).is_ok());
+}
+
+#[test]
+fn scale_about_an_origin_keeps_the_origin_in_place() {
+    let identity = Transform3D::identity();
+    let scaled = compose_matrix(&identity, &MatrixOp::scale(2., 3., 4., 10., 20., 30.));
+    assert_eq!(
+        scaled.transform_point3d(Point3D::new(10., 20., 30.)),
+        Some(Point3D::new(10., 20., 30.))
+    );
+    let scaled = compose_matrix(&identity, &MatrixOp::scale_3d(2., 10., 20., 30.));
+    assert_eq!(
+        scaled.transform_point3d(Point3D::new(10., 20., 30.)),
+        Some(Point3D::new(10., 20., 30.))
+    );
+}
+
+// scaleSelf() and scale(), which calls it.
+#[test]
+fn nan_origin_of_scale_propagates_by_default() {
+    for origin in [[f64::NAN, 0., 0.], [0., f64::NAN, 0.], [0., 0., f64::NAN]] {
+        assert!(validate_scale_origin(origin, false).is_ok());
+        let ops = MatrixOp::scale(2., 3., 4., origin[0], origin[1], origin[2]);
+        let matrix = compose_matrix(&Transform3D::identity(), &ops);
+        assert!(matrix_to_row_major(&matrix)
+            .iter()
+            .any(|component| component.is_nan()));
+    }
+}
+
+// scale3dSelf() and scale3d(), which calls it.
+#[test]
+fn nan_origin_of_scale_3d_propagates_by_default() {
+    for origin in [[f64::NAN, 0., 0.], [0., f64::NAN, 0.], [0., 0., f64::NAN]] {
+        assert!(validate_scale_origin(origin, false).is_ok());
+        let ops = MatrixOp::scale_3d(2., origin[0], origin[1], origin[2]);
+        let matrix = compose_matrix(&Transform3D::identity(), &ops);
+        assert!(matrix_to_row_major(&matrix)
+            .iter()
+            .any(|component| component.is_nan()));
+    }
+}
+
+// scale2d(), whose origin has no Z coordinate.
+#[test]
+fn nan_origin_of_scale_2d_propagates_by_default() {
+    for (origin_x, origin_y) in [(f64::NAN, 0.), (0., f64::NAN)] {
+        assert!(validate_scale_origin([origin_x, origin_y, 0.], false).is_ok());
+        let ops = MatrixOp::scale_2d(2., 3., origin_x, origin_y);
+        let matrix = compose_matrix(&Transform3D::identity(), &ops);
+        assert!(matrix.m41.is_nan() || matrix.m42.is_nan());
+    }
+}
+
+#[test]
+fn nan_origin_of_scale_2d_is_rejected_when_strict() {
+    for (origin_x, origin_y) in [(f64::NAN, 0.), (0., f64::NAN)] {
+        assert!(matches!(
+            validate_scale_origin([origin_x, origin_y, 0.], true),
+            Err(Error::Type(_))
+        ));
+    }
+    assert!(validate_scale_origin([1., 2., 0.], true).is_ok());
+}
+
+/// The init dict with all sixteen components of `matrix`, as `toJSON()`
+/// would give it.
+fn init_from_matrix(matrix: &Transform3D<f64>) -> DOMMatrixInit {
+    let mut init = DOMMatrixInit::empty();
+    init.parent.m11 = Some(matrix.m11);
+    init.parent.m12 = Some(matrix.m12);
+    init.m13 = matrix.m13;
+    init.m14 = matrix.m14;
+    init.parent.m21 = Some(matrix.m21);
+    init.parent.m22 = Some(matrix.m22);
+    init.m23 = matrix.m23;
+    init.m24 = matrix.m24;
+    init.m31 = matrix.m31;
+    init.m32 = matrix.m32;
+    init.m33 = matrix.m33;
+    init.m34 = matrix.m34;
+    init.parent.m41 = Some(matrix.m41);
+    init.parent.m42 = Some(matrix.m42);
+    init.m43 = matrix.m43;
+    init.m44 = matrix.m44;
+    init
+}
+
+#[test]
+fn setting_a_3d_component_makes_a_matrix_3d() {
+    let matrix = DOMMatrixReadOnly::new_inherited(true, Transform3D::identity());
+    // 0, -0 and 1 on the diagonal are what a 2D matrix has there.
+    matrix.set_m13(-0.);
+    matrix.set_m33(1.);
+    matrix.set_m41(7.);
+    assert!(matrix.is2D());
+    matrix.set_m43(f64::NAN);
+    assert!(!matrix.is2D());
+
+    let matrix = DOMMatrixReadOnly::new_inherited(true, Transform3D::identity());
+    matrix.set_m44(2.);
+    assert!(!matrix.is2D());
+}
+
+#[test]
+fn multiplying_after_setting_m13_keeps_the_3d_components() {
+    // `m.m13 = 5; m.multiplySelf(other)` with a 2D `other`: setting m13 made
+    // `m` 3D, so neither side takes the 2D fast path.
+    let mut with_m13 = Transform3D::identity();
+    with_m13.m13 = 5.;
+    let other = Transform3D::new_2d(2., 1., -1., 3., 10., 20.);
+
+    let matrix = DOMMatrixReadOnly::new_inherited(true, Transform3D::identity());
+    matrix.set_m13(5.);
+    matrix
+        .multiply_self(&init_from_matrix(&other))
+        .expect("init should be valid");
+    assert!(!matrix.is2D());
+    assert_eq!(matrix.M13(), 5.);
+    assert_eq!(*matrix.matrix(), other.then(&with_m13));
+
+    let matrix = DOMMatrixReadOnly::new_inherited(true, Transform3D::identity());
+    matrix.set_m13(5.);
+    matrix
+        .pre_multiply_self(&init_from_matrix(&other))
+        .expect("init should be valid");
+    assert!(!matrix.is2D());
+    assert_eq!(*matrix.matrix(), with_m13.then(&other));
+}
+
+#[test]
+fn multiply_by_own_components_squares_the_matrix() {
+    // What `m.multiply(m)` does: the argument is converted to its own
+    // matrix before `m` is touched.
+    let matrix = Transform3D::rotation(1., 0., 0., Angle::degrees(30.))
+        .then(&Transform3D::translation(1., 2., 3.));
+    let (is_2d, other) =
+        dommatrixinit_to_matrix(&init_from_matrix(&matrix)).expect("init should be valid");
+    assert!(!is_2d);
+    assert_eq!(other, matrix);
+    assert_eq!(
+        post_multiply(&matrix, false, &other, false),
+        matrix.then(&matrix)
+    );
+}
+
+#[test]
+fn multiplying_a_matrix_by_itself_does_not_double_borrow() {
+    let components = Transform3D::rotation(1., 0., 0., Angle::degrees(30.))
+        .then(&Transform3D::translation(1., 2., 3.));
+    let matrix = DOMMatrixReadOnly::new_inherited(false, components);
+    matrix.multiply_self_by(&matrix);
+    assert_eq!(*matrix.matrix(), components.then(&components));
+    assert!(!matrix.is2D());
+}
+
+#[test]
+fn transform_points_matches_transforming_each_point() {
+    let matrix = Transform3D::rotation(0., 0., 1., Angle::degrees(30.))
+        .then(&Transform3D::translation(5., -2., 1.));
+    let points = [[1., 2., 3., 1.], [-4., 0.5, 0., 1.], [0., 0., 0., 2.]];
+    let transformed = transform_points(&matrix, &points);
+    assert_eq!(transformed.len(), 3);
+    for (point, result) in points.iter().zip(transformed) {
+        assert_eq!(
+            result,
+            transform_homogeneous_point(&matrix, *point, /* perspective_divide */ false)
+        );
+    }
+}
+
+#[test]
+fn multiply_by_array_entries_matches_multiply_by_init() {
+    let matrix = Transform3D::translation(1., 2., 3.);
+    let array: [f32; 16] = [
+        2., 0., 0., 0., 0., 3., 0., 0., 0., 0., 4., 0., 0.5, 0.25, 0., 1.,
+    ];
+    let entries: Vec<f64> = array.iter().map(|&x| x as f64).collect();
+    let (array_is_2d, from_array) = entries_to_matrix(&entries).expect("16 entries are valid");
+    let (init_is_2d, from_init) =
+        dommatrixinit_to_matrix(&init_from_matrix(&from_array)).expect("init should be valid");
+    assert!(!array_is_2d);
+    assert_eq!(array_is_2d, init_is_2d);
+    assert_eq!(
+        post_multiply(&matrix, false, &from_array, array_is_2d),
+        post_multiply(&matrix, false, &from_init, init_is_2d)
+    );
+}
+
+#[test]
+fn invert_2d_matches_full_inverse() {
+    let matrix = Transform3D::new_2d(2., 1., -1., 3., 10., -5.);
+    let (is_2d, inverse) = invert_matrix(&matrix, true);
+    assert!(is_2d);
+    let full = matrix.inverse().expect("matrix should be invertible");
+    assert_close(&inverse.to_array(), &full.to_array());
+    assert_close(
+        &inverse.then(&matrix).to_array(),
+        &Transform3D::<f64>::identity().to_array(),
+    );
+}
+
+#[test]
+fn invert_singular_2d_is_nan() {
+    // The second column is twice the first, so the determinant is 0.
+    let matrix = Transform3D::new_2d(1., 2., 2., 4., 5., 6.);
+    let (is_2d, inverse) = invert_matrix(&matrix, true);
+    assert!(!is_2d);
+    assert!(inverse
+        .to_array()
+        .iter()
+        .all(|component| component.is_nan()));
+}
+
+#[test]
+fn inverting_a_matrix_made_3d_by_its_setters_is_a_full_inverse() {
+    let original = Transform3D::new_2d(2., 1., -1., 3., 10., -5.);
+    let matrix = DOMMatrixReadOnly::new_inherited(true, original);
+    matrix.set_m13(0.5);
+    matrix.set_m43(4.);
+    let made_3d = *matrix.matrix();
+
+    matrix.invert_self();
+    assert!(!matrix.is2D());
+    let full = made_3d.inverse().expect("matrix should be invertible");
+    assert_close(&matrix.matrix().to_array(), &full.to_array());
+    assert_close(
+        &matrix.matrix().then(&made_3d).to_array(),
+        &Transform3D::<f64>::identity().to_array(),
+    );
+}
+
+#[test]
+fn cached_rotation_quaternion_matches_decomposition() {
+    let matrix = Transform3D::rotation(0., 0., 1., Angle::degrees(40.));
+    let cache = Cell::new(None);
+    let fresh = decompose_matrix(&matrix)
+        .expect("rotation should decompose")
+        .quaternion;
+    assert_eq!(cached_rotation_quaternion(&cache, &matrix), Some(fresh));
+    assert_eq!(cache.get(), Some(fresh));
+}
+
+#[test]
+fn cached_rotation_quaternion_is_recomputed_once_cleared() {
+    let matrix = Transform3D::rotation(0., 0., 1., Angle::degrees(40.));
+    let cache = Cell::new(None);
+    let before = cached_rotation_quaternion(&cache, &matrix).expect("rotation should decompose");
+
+    // What a mutation like `rotate_self` does: change the matrix and drop
+    // the cached quaternion.
+    let rotated = Transform3D::rotation(0., 0., 1., Angle::degrees(30.)).then(&matrix);
+    assert_eq!(cached_rotation_quaternion(&cache, &rotated), Some(before));
+    cache.set(None);
+    let after = cached_rotation_quaternion(&cache, &rotated).expect("rotation should decompose");
+    assert_ne!(after, before);
+    assert_eq!(
+        Some(after),
+        decompose_matrix(&rotated).map(|decomposed| decomposed.quaternion)
+    );
+}
+
+#[test]
+fn stringifier_round_trips_tiny_components() {
+    // Number::toString switches to exponential notation below 1e-6, and the
+    // CSS number syntax accepts it back.
+    let matrix = Transform3D::translation(1e-8, -2.5e-7, 0.);
+    let serialized = serialize_matrix(&matrix, true).expect("matrix should be finite");
+    assert_eq!(serialized, "matrix(1, 0, 0, 1, 1e-8, -2.5e-7)");
+    let (is_2d, parsed) = transform_to_matrix(serialized).expect("serialization should parse");
+    assert!(is_2d);
+    assert!((parsed.m41 - 1e-8).abs() <= 1e-8 * 1e-6, "{}", parsed.m41);
+    assert!(
+        (parsed.m42 + 2.5e-7).abs() <= 2.5e-7 * 1e-6,
+        "{}",
+        parsed.m42
+    );
+}
+
+#[test]
+fn matrix3d_string_of_2d_matrix_has_all_components() {
+    // What toMatrix3dString() gives for matrix(2, 0.5, -1, 3, 10, 20).
+    let matrix = Transform3D::new_2d(2., 0.5, -1., 3., 10., 20.);
+    assert_eq!(
+        serialize_matrix(&matrix, false).ok(),
+        Some("matrix3d(2, 0.5, 0, 0, -1, 3, 0, 0, 0, 0, 1, 0, 10, 20, 0, 1)".to_owned())
+    );
+}
+
+#[test]
+fn mutating_a_clone_leaves_the_original_unchanged() {
+    let original = Transform3D::translation(10., 20., 0.);
+    let (is_2d, mut clone) = clone_matrix(true, &original);
+    assert!(is_2d);
+    assert_eq!(clone, original);
+    clone = compose_matrix(&clone, &[MatrixOp::Translate(5., 5., 0.)]);
+    assert_eq!(clone, Transform3D::translation(15., 25., 0.));
+    assert_eq!(original, Transform3D::translation(10., 20., 0.));
+}
+
+#[test]
+fn clone_keeps_is_2d_and_every_component() {
+    let mut original = Transform3D::translation(1., 2., 3.);
+    original.m12 = -0.;
+    original.m21 = f64::NAN;
+    let (is_2d, clone) = clone_matrix(false, &original);
+    assert!(!is_2d);
+    let bits = |matrix: &Transform3D<f64>| matrix_to_row_major(matrix).map(f64::to_bits);
+    assert_eq!(bits(&clone), bits(&original));
+}
+
+#[test]
+fn translating_along_z_makes_a_matrix_3d() {
+    let identity = Transform3D::identity();
+    assert_eq!(
+        translated_matrix(true, &identity, 1., 2., 0.),
+        (true, Transform3D::translation(1., 2., 0.))
+    );
+    assert_eq!(
+        translated_matrix(true, &identity, 1., 2., 3.),
+        (false, Transform3D::translation(1., 2., 3.))
+    );
+}
+
+#[test]
+fn every_mutation_sets_the_dirty_flag_until_taken() {
+    let translation = init_from_matrix(&Transform3D::translation(1., 2., 0.));
+    let mutations: Vec<(&str, Box<dyn Fn(&DOMMatrixReadOnly)>)> = vec![
+        ("m11", Box::new(|m| m.set_m11(2.))),
+        ("m12", Box::new(|m| m.set_m12(2.))),
+        ("m13", Box::new(|m| m.set_m13(2.))),
+        ("m14", Box::new(|m| m.set_m14(2.))),
+        ("m21", Box::new(|m| m.set_m21(2.))),
+        ("m22", Box::new(|m| m.set_m22(2.))),
+        ("m23", Box::new(|m| m.set_m23(2.))),
+        ("m24", Box::new(|m| m.set_m24(2.))),
+        ("m31", Box::new(|m| m.set_m31(2.))),
+        ("m32", Box::new(|m| m.set_m32(2.))),
+        ("m33", Box::new(|m| m.set_m33(2.))),
+        ("m34", Box::new(|m| m.set_m34(2.))),
+        ("m41", Box::new(|m| m.set_m41(2.))),
+        ("m42", Box::new(|m| m.set_m42(2.))),
+        ("m43", Box::new(|m| m.set_m43(2.))),
+        ("m44", Box::new(|m| m.set_m44(2.))),
+        (
+            "set_component",
+            Box::new(|m| assert!(m.set_component(2, 3, 2.).is_ok())),
+        ),
+        (
+            "multiplySelf",
+            Box::new(|m| assert!(m.multiply_self(&translation).is_ok())),
+        ),
+        (
+            "multiplySelf by entries",
+            Box::new(|m| {
+                assert!(m
+                    .multiply_self_by_entries(&[2., 0., 0., 2., 0., 0.])
+                    .is_ok())
+            }),
+        ),
+        (
+            "preMultiplySelf",
+            Box::new(|m| assert!(m.pre_multiply_self(&translation).is_ok())),
+        ),
+        ("translateSelf", Box::new(|m| m.translate_self(1., 2., 3.))),
+        (
+            "scaleSelf",
+            Box::new(|m| m.scale_self(2., None, 1., 0., 0., 0.)),
+        ),
+        ("scale3dSelf", Box::new(|m| m.scale_3d_self(2., 0., 0., 0.))),
+        ("rotateSelf", Box::new(|m| m.rotate_self(30., None, None))),
+        (
+            "rotateFromVectorSelf",
+            Box::new(|m| m.rotate_from_vector_self(1., 1.)),
+        ),
+        (
+            "rotateAxisAngleSelf",
+            Box::new(|m| m.rotate_axis_angle_self(1., 0., 0., 30.)),
+        ),
+        ("skewXSelf", Box::new(|m| m.skew_x_self(30.))),
+        ("skewYSelf", Box::new(|m| m.skew_y_self(30.))),
+        ("invertSelf", Box::new(|m| m.invert_self())),
+    ];
+    for (name, mutate) in &mutations {
+        let matrix = DOMMatrixReadOnly::new_inherited(true, Transform3D::identity());
+        assert!(!matrix.take_dirty(), "a new matrix is not dirty");
+        mutate(&matrix);
+        assert!(matrix.take_dirty(), "{} should set the dirty flag", name);
+        assert!(
+            !matrix.take_dirty(),
+            "taking the flag after {} clears it",
+            name
+        );
+    }
+}
+
+#[test]
+fn mutating_a_matrix_drops_its_cached_quaternion() {
+    let matrix = DOMMatrixReadOnly::new_inherited(true, Transform3D::identity());
+    matrix.rotate_self(40., None, None);
+    let before = matrix.rotation_quaternion();
+    assert!(before.is_some());
+
+    matrix.rotate_self(50., None, None);
+    let after = decompose_matrix(&matrix.matrix())
+        .expect("rotation should decompose")
+        .quaternion;
+    assert_eq!(matrix.rotation_quaternion(), Some(after));
+    assert_ne!(before, Some(after));
+}
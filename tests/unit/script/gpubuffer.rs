@@ -0,0 +1,518 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::{Cell, RefCell};
+use std::ops::Range;
+use std::ptr;
+use std::rc::Rc;
+
+use script::test::gpubuffer::{
+    debug_assert_buffer_state_transition, debug_mapped_ranges, destroy_step, finalize_mapping,
+    flush_range_bytes, flush_range_payload, is_map_read, is_valid_buffer_state_transition,
+    is_valid_map_range, map_async_state_error, map_range_size, mapped_buffer_info,
+    release_mapping_view, requested_mapped_range, retain_mapping_for_view,
+    state_after_map_response, unmap_payload, validate_buffer_size,
+    validate_mapped_at_creation_size, validate_mapped_range, write_back_ranges, DestroyStep, Error,
+    GPUBufferMapInfo, GPUBufferState, GPUMapModeConstants, MappedRangeError,
+};
+
+/// The map info `mapAsync(mode, ...)` sets up for `mapping_range`, holding
+/// `mapping`, before `getMappedRange()` is called.
+fn map_info(mode: u32, mapping_range: Range<u64>, mapping: Vec<u8>) -> GPUBufferMapInfo {
+    GPUBufferMapInfo {
+        mapping: Rc::new(RefCell::new(mapping)),
+        mapping_range,
+        mapped_ranges: vec![],
+        js_buffers: vec![],
+        map_mode: Some(mode),
+    }
+}
+
+#[test]
+fn mid_buffer_range_of_mapped_at_creation_buffer() {
+    // A buffer mapped at creation is mapped in full.
+    assert_eq!(validate_mapped_range(&(0..64), &[], 16, 16), Ok(()));
+    assert_eq!(validate_mapped_range(&(0..64), &[0..16], 16, 16), Ok(()));
+}
+
+#[test]
+fn ranges_outside_the_mapping() {
+    assert_eq!(
+        validate_mapped_range(&(16..32), &[], 0, 16),
+        Err(MappedRangeError::BeforeMapping)
+    );
+    assert_eq!(
+        validate_mapped_range(&(16..32), &[], 24, 16),
+        Err(MappedRangeError::AfterMapping)
+    );
+}
+
+#[test]
+fn overlapping_or_misaligned_ranges() {
+    assert_eq!(
+        validate_mapped_range(&(0..64), &[8..24], 16, 16),
+        Err(MappedRangeError::Overlapping)
+    );
+    assert_eq!(
+        validate_mapped_range(&(0..64), &[], 4, 16),
+        Err(MappedRangeError::Misaligned)
+    );
+    assert_eq!(
+        validate_mapped_range(&(0..64), &[], 8, 6),
+        Err(MappedRangeError::Misaligned)
+    );
+}
+
+#[test]
+fn adjacent_ranges_do_not_overlap() {
+    assert_eq!(validate_mapped_range(&(0..256), &[], 0, 128), Ok(()));
+    assert_eq!(
+        validate_mapped_range(&(0..256), &[0..128], 128, 128),
+        Ok(())
+    );
+    // The other way around too: the new range ends where the old one starts.
+    assert_eq!(
+        validate_mapped_range(&(0..256), &[128..256], 0, 128),
+        Ok(())
+    );
+}
+
+#[test]
+fn partially_overlapping_ranges_are_rejected() {
+    assert_eq!(
+        validate_mapped_range(&(0..256), &[0..128], 64, 128),
+        Err(MappedRangeError::Overlapping)
+    );
+    assert_eq!(
+        validate_mapped_range(&(0..256), &[64..192], 0, 128),
+        Err(MappedRangeError::Overlapping)
+    );
+}
+
+#[test]
+fn range_below_a_nonzero_mapping_offset() {
+    // A buffer mapped with `mapAsync(mode, 256, 256)`.
+    assert_eq!(
+        validate_mapped_range(&(256..512), &[], 0, 64),
+        Err(MappedRangeError::BeforeMapping)
+    );
+    assert_eq!(
+        validate_mapped_range(&(256..512), &[], 0, 512),
+        Err(MappedRangeError::BeforeMapping)
+    );
+}
+
+#[test]
+fn requested_mapped_range_defaults_to_the_rest_of_the_buffer() {
+    assert_eq!(requested_mapped_range(64, 16, None), Some(16..64));
+    assert_eq!(requested_mapped_range(64, 16, Some(8)), Some(16..24));
+    // Without a size, the offset has to be within the buffer.
+    assert_eq!(requested_mapped_range(64, 64, None), None);
+    assert_eq!(requested_mapped_range(64, 128, None), None);
+}
+
+#[test]
+fn requested_mapped_range_does_not_overflow() {
+    assert_eq!(requested_mapped_range(64, 8, Some(u64::MAX)), None);
+    assert_eq!(requested_mapped_range(64, u64::MAX, Some(1)), None);
+    assert_eq!(
+        requested_mapped_range(64, u64::MAX - 8, Some(8)),
+        Some(u64::MAX - 8..u64::MAX)
+    );
+}
+
+#[test]
+fn map_ranges_stay_within_the_buffer() {
+    assert!(is_valid_map_range(64, 0, 64));
+    assert!(is_valid_map_range(64, 32, 32));
+    assert!(!is_valid_map_range(64, 32, 40));
+    assert!(!is_valid_map_range(64, u64::MAX - 7, 8));
+}
+
+#[test]
+fn default_map_size_extends_to_the_end_of_the_buffer() {
+    assert_eq!(map_range_size(256, 0, None), 256);
+    assert_eq!(
+        map_range_size(256, 0, None),
+        map_range_size(256, 0, Some(256))
+    );
+    assert_eq!(map_range_size(256, 64, None), 192);
+    assert!(is_valid_map_range(256, 64, map_range_size(256, 64, None)));
+}
+
+#[test]
+fn default_map_size_at_the_end_of_the_buffer_is_empty() {
+    assert_eq!(map_range_size(256, 256, None), 0);
+    assert!(is_valid_map_range(256, 256, map_range_size(256, 256, None)));
+    // Past the end the range is empty too, but starts outside the buffer.
+    assert_eq!(map_range_size(256, 264, None), 0);
+    assert!(!is_valid_map_range(
+        256,
+        264,
+        map_range_size(256, 264, None)
+    ));
+}
+
+#[test]
+fn only_unmapped_buffers_can_be_mapped() {
+    assert!(map_async_state_error(GPUBufferState::Unmapped).is_none());
+    assert!(matches!(
+        map_async_state_error(GPUBufferState::Mapped),
+        Some(("Buffer is not Unmapped", Error::Abort))
+    ));
+    assert!(matches!(
+        map_async_state_error(GPUBufferState::MappingPending),
+        Some(("Buffer is not Unmapped", Error::Abort))
+    ));
+}
+
+#[test]
+fn mapping_a_destroyed_buffer_fails() {
+    assert!(is_valid_buffer_state_transition(
+        GPUBufferState::Unmapped,
+        GPUBufferState::Destroyed
+    ));
+    assert!(matches!(
+        map_async_state_error(GPUBufferState::Destroyed),
+        Some(("Buffer is destroyed", Error::Operation))
+    ));
+}
+
+#[test]
+fn destroying_a_buffer_twice_reaches_the_device_once() {
+    assert_eq!(destroy_step(GPUBufferState::Unmapped), DestroyStep::Destroy);
+    assert!(is_valid_buffer_state_transition(
+        GPUBufferState::Unmapped,
+        GPUBufferState::Destroyed
+    ));
+    // The second destroy() sends nothing, so an error scope pushed around
+    // both calls has nothing to capture.
+    assert_eq!(
+        destroy_step(GPUBufferState::Destroyed),
+        DestroyStep::Nothing
+    );
+}
+
+#[test]
+fn destroying_a_mapped_buffer_unmaps_it_first() {
+    for state in [
+        GPUBufferState::Mapped,
+        GPUBufferState::MappedAtCreation,
+        GPUBufferState::MappingPending,
+    ] {
+        assert_eq!(destroy_step(state), DestroyStep::UnmapThenDestroy);
+    }
+}
+
+#[test]
+fn failed_map_leaves_the_buffer_unmapped() {
+    let state = state_after_map_response(false);
+    assert!(is_valid_buffer_state_transition(
+        GPUBufferState::MappingPending,
+        state
+    ));
+    assert_eq!(state, GPUBufferState::Unmapped);
+    // So unmap() has no pending mapping to reject, destroy() doesn't unmap
+    // first, and mapAsync() can be called again.
+    assert_eq!(destroy_step(state), DestroyStep::Destroy);
+    assert!(map_async_state_error(state).is_none());
+}
+
+#[test]
+fn successful_map_leaves_the_buffer_mapped() {
+    let state = state_after_map_response(true);
+    assert!(is_valid_buffer_state_transition(
+        GPUBufferState::MappingPending,
+        state
+    ));
+    assert_eq!(state, GPUBufferState::Mapped);
+}
+
+#[test]
+fn map_ranges_must_be_aligned() {
+    // The offset has to be a multiple of 8.
+    assert!(!is_valid_map_range(64, 4, 8));
+    // The size has to be a multiple of 4.
+    assert!(!is_valid_map_range(64, 8, 6));
+    assert!(is_valid_map_range(64, 8, 4));
+}
+
+#[test]
+fn oversized_buffer_is_a_validation_error() {
+    assert!(validate_buffer_size(256, 256).is_ok());
+    assert!(validate_buffer_size(257, 256).is_err());
+}
+
+#[test]
+fn only_mapped_ranges_are_written_back() {
+    // Two disjoint ranges, handed out in reverse order, leave the gap between
+    // them and the tail of the mapping untouched.
+    assert_eq!(write_back_ranges(&[32..48, 0..16]), [0..16, 32..48]);
+    assert!(write_back_ranges(&[]).is_empty());
+}
+
+#[test]
+fn adjacent_mapped_ranges_are_written_back_together() {
+    assert_eq!(write_back_ranges(&[16..24, 0..16, 40..48]), [0..24, 40..48]);
+}
+
+#[test]
+fn each_mapped_range_view_holds_its_own_reference() {
+    // Two views, as from getMappedRange(0, 128) and getMappedRange(256, 128).
+    let mapping = Rc::new(RefCell::new(vec![0u8; 512]));
+    let first = retain_mapping_for_view(&mapping);
+    let second = retain_mapping_for_view(&mapping);
+    assert_eq!(Rc::strong_count(&mapping), 3);
+
+    // Detaching one view on unmap leaves the other one's bytes alive.
+    unsafe { release_mapping_view(ptr::null_mut(), second) };
+    assert_eq!(Rc::strong_count(&mapping), 2);
+    unsafe { release_mapping_view(ptr::null_mut(), first) };
+    assert_eq!(Rc::strong_count(&mapping), 1);
+}
+
+#[test]
+fn finalizing_a_mapped_buffer_leaves_its_views_valid() {
+    // getMappedRange() handed a view of a mapped buffer to script, then the
+    // buffer was collected without being unmapped.
+    let mut map_info = map_info(GPUMapModeConstants::WRITE, 0..64, vec![7u8; 64]);
+    map_info.mapped_ranges.push(0..64);
+    let view = retain_mapping_for_view(&map_info.mapping);
+    let mapping = Rc::downgrade(&map_info.mapping);
+
+    assert!(finalize_mapping(
+        GPUBufferState::Mapped,
+        Some(&mut map_info)
+    ));
+    drop(map_info);
+    // The view's reference keeps the bytes alive past the buffer.
+    let bytes = mapping.upgrade().expect("the view keeps the mapping alive");
+    assert_eq!(*bytes.borrow(), vec![7u8; 64]);
+    drop(bytes);
+
+    unsafe { release_mapping_view(ptr::null_mut(), view) };
+    assert!(mapping.upgrade().is_none());
+}
+
+#[test]
+fn finalizing_a_buffer_destroys_it_only_if_destroy_was_not_called() {
+    for state in [
+        GPUBufferState::Mapped,
+        GPUBufferState::MappedAtCreation,
+        GPUBufferState::MappingPending,
+        GPUBufferState::Unmapped,
+    ] {
+        assert!(finalize_mapping(state, None), "{:?}", state);
+    }
+    // destroy() already sent the destroy request, so finalizing the buffer
+    // doesn't send a second one.
+    assert_eq!(destroy_step(GPUBufferState::Unmapped), DestroyStep::Destroy);
+    assert!(!finalize_mapping(GPUBufferState::Destroyed, None));
+}
+
+#[test]
+fn buffer_state_machine_allows_mapping_and_destroying() {
+    // mapAsync(), its resolution, unmap(), then destroy().
+    let states = [
+        GPUBufferState::Unmapped,
+        GPUBufferState::MappingPending,
+        GPUBufferState::Mapped,
+        GPUBufferState::Unmapped,
+        GPUBufferState::Destroyed,
+    ];
+    for transition in states.windows(2) {
+        debug_assert_buffer_state_transition(transition[0], transition[1]);
+    }
+    // Unmapping a buffer mapped at creation, or with a mapping still pending.
+    assert!(is_valid_buffer_state_transition(
+        GPUBufferState::MappedAtCreation,
+        GPUBufferState::Unmapped
+    ));
+    assert!(is_valid_buffer_state_transition(
+        GPUBufferState::MappingPending,
+        GPUBufferState::Unmapped
+    ));
+}
+
+#[test]
+fn buffer_state_machine_rejects_illegal_transitions() {
+    assert!(!is_valid_buffer_state_transition(
+        GPUBufferState::Unmapped,
+        GPUBufferState::Mapped
+    ));
+    assert!(!is_valid_buffer_state_transition(
+        GPUBufferState::Destroyed,
+        GPUBufferState::MappingPending
+    ));
+    assert!(!is_valid_buffer_state_transition(
+        GPUBufferState::Destroyed,
+        GPUBufferState::Destroyed
+    ));
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "Illegal GPUBuffer state transition")]
+fn illegal_buffer_state_transition_asserts_in_debug_builds() {
+    debug_assert_buffer_state_transition(GPUBufferState::Mapped, GPUBufferState::MappingPending);
+}
+
+#[test]
+fn unmap_payload_is_empty_for_read_mappings() {
+    let mapping = vec![7u8; 64];
+    assert!(unmap_payload(&mapping, 0, true, &[0..64]).is_empty());
+}
+
+#[test]
+fn unmap_payload_is_empty_without_mapped_ranges() {
+    let mapping = vec![7u8; 64];
+    assert!(unmap_payload(&mapping, 0, false, &[]).is_empty());
+}
+
+#[test]
+fn unmap_payload_ends_with_the_last_write_range() {
+    // A mapping of 256..512, of which only 256..264 and 296..320 were handed
+    // out: the bytes past 320 are never copied back.
+    let mapping: Vec<u8> = (0..=255).collect();
+    let write_ranges = write_back_ranges(&[296..320, 256..264]);
+    let payload = unmap_payload(&mapping, 256, false, &write_ranges);
+    assert_eq!(payload, &mapping[..64]);
+}
+
+#[test]
+fn flush_range_sends_the_bytes_of_the_range() {
+    // A mapping of 256..512 whose bytes are their offset within it.
+    let mapping: Vec<u8> = (0..=255).collect();
+    assert_eq!(
+        flush_range_bytes(&mapping, &(256..512), 264, 8),
+        Some(&mapping[8..16])
+    );
+    assert_eq!(
+        flush_range_bytes(&mapping, &(256..512), 256, 256),
+        Some(&mapping[..])
+    );
+}
+
+#[test]
+fn flush_range_outside_the_mapping_is_rejected() {
+    let mapping = vec![0u8; 256];
+    assert_eq!(flush_range_bytes(&mapping, &(256..512), 248, 16), None);
+    assert_eq!(flush_range_bytes(&mapping, &(256..512), 504, 16), None);
+    assert_eq!(flush_range_bytes(&mapping, &(256..512), u64::MAX, 16), None);
+}
+
+#[test]
+fn flush_range_keeps_the_buffer_mapped() {
+    // mapAsync(WRITE, 256, 256) succeeded, and script wrote each byte's offset
+    // within the mapping.
+    let state = Cell::new(state_after_map_response(true));
+    let mut map_info = map_info(GPUMapModeConstants::WRITE, 256..512, (0..=255).collect());
+    map_info.mapped_ranges.push(256..512);
+
+    for _ in 0..2 {
+        let (offset, bytes) =
+            flush_range_payload(&state, Some(&map_info), 264, 8).expect("a flushable range");
+        assert_eq!(offset, 264);
+        assert_eq!(bytes, (8..16).collect::<Vec<u8>>());
+        assert_eq!(state.get(), GPUBufferState::Mapped);
+    }
+    assert_eq!(debug_mapped_ranges(Some(&map_info)), vec![256..512]);
+}
+
+#[test]
+fn flush_range_needs_a_writable_mapping() {
+    let state = Cell::new(GPUBufferState::Mapped);
+    let mut map_info = map_info(GPUMapModeConstants::READ, 0..64, vec![0; 64]);
+    assert!(matches!(
+        flush_range_payload(&state, Some(&map_info), 0, 16),
+        Err(Error::Operation)
+    ));
+
+    map_info.map_mode = Some(GPUMapModeConstants::WRITE);
+    assert!(matches!(
+        flush_range_payload(&state, Some(&map_info), 56, 16),
+        Err(Error::Operation)
+    ));
+    state.set(GPUBufferState::Unmapped);
+    assert!(matches!(
+        flush_range_payload(&state, None, 0, 16),
+        Err(Error::Operation)
+    ));
+    assert_eq!(state.get(), GPUBufferState::Unmapped);
+}
+
+#[test]
+fn mapped_at_creation_size_must_be_a_multiple_of_4() {
+    assert!(validate_mapped_at_creation_size(true, 16).is_ok());
+    assert!(validate_mapped_at_creation_size(true, 6).is_err());
+    // The size of a buffer that isn't mapped at creation isn't restricted.
+    assert!(validate_mapped_at_creation_size(false, 6).is_ok());
+}
+
+#[test]
+fn mapped_range_needs_a_mapped_buffer() {
+    for state in [GPUBufferState::Mapped, GPUBufferState::MappedAtCreation] {
+        assert!(matches!(mapped_buffer_info(state, Some(())), Ok(())));
+        assert!(matches!(
+            mapped_buffer_info::<()>(state, None),
+            Err(Error::Operation)
+        ));
+    }
+    for state in [
+        GPUBufferState::Unmapped,
+        GPUBufferState::MappingPending,
+        GPUBufferState::Destroyed,
+    ] {
+        assert!(matches!(
+            mapped_buffer_info(state, Some(())),
+            Err(Error::Operation)
+        ));
+    }
+}
+
+#[test]
+fn bytes_written_to_a_mapped_at_creation_buffer_are_sent_on_unmap() {
+    // createBuffer({ size: 16, mappedAtCreation: true }) maps the whole buffer
+    // with no map mode.
+    assert!(validate_mapped_at_creation_size(true, 16).is_ok());
+    let mapping_range = 0..16;
+    let mut mapping = vec![0u8; 16];
+    let mut mapped_ranges = vec![];
+    assert!(mapped_buffer_info(GPUBufferState::MappedAtCreation, Some(&mapping_range)).is_ok());
+
+    // getMappedRange(4, 8), then script writes to the view.
+    let requested = requested_mapped_range(16, 4, Some(8)).expect("a range within the buffer");
+    assert_eq!(
+        validate_mapped_range(&mapping_range, &mapped_ranges, 4, 8),
+        Ok(())
+    );
+    mapping[4..12].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+    mapped_ranges.push(requested);
+
+    // unmap() writes the range back.
+    let is_map_read = is_map_read(None);
+    assert!(!is_map_read);
+    let write_ranges = write_back_ranges(&mapped_ranges);
+    assert_eq!(write_ranges, [4..12]);
+    let payload = unmap_payload(&mapping, mapping_range.start, is_map_read, &write_ranges);
+    assert_eq!(&payload[4..12], &[1, 2, 3, 4, 5, 6, 7, 8]);
+}
+
+#[test]
+fn debug_snapshot_reports_the_ranges_handed_out_since_mapping() {
+    // mapAsync(READ, 0, 64) sets up the map info, and the map succeeds.
+    let mut map_info = map_info(GPUMapModeConstants::READ, 0..64, vec![0; 64]);
+    assert_eq!(state_after_map_response(true), GPUBufferState::Mapped);
+    assert!(debug_mapped_ranges(Some(&map_info)).is_empty());
+
+    // getMappedRange(0, 16), then getMappedRange(32).
+    for (offset, size) in [(0, Some(16)), (32, None)] {
+        let requested = requested_mapped_range(64, offset, size).expect("a range in the buffer");
+        map_info.mapped_ranges.push(requested);
+    }
+    assert_eq!(debug_mapped_ranges(Some(&map_info)), [0..16, 32..64]);
+
+    // unmap() drops the map info.
+    assert!(debug_mapped_ranges(None).is_empty());
+}
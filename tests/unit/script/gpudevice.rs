@@ -0,0 +1,29 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::gpudevice::{
+    convert_label, GPUBufferDescriptor, GPUObjectDescriptorBase, USVString,
+};
+
+#[test]
+fn buffer_label_reaches_the_creation_descriptor() {
+    let descriptor = GPUBufferDescriptor {
+        parent: GPUObjectDescriptorBase {
+            label: Some(USVString("vertices".to_owned())),
+        },
+        size: 16,
+        usage: 0,
+        mappedAtCreation: false,
+    };
+    assert_eq!(
+        convert_label(&descriptor.parent).as_deref(),
+        Some("vertices")
+    );
+}
+
+#[test]
+fn unlabeled_buffer_has_no_label() {
+    let descriptor = GPUObjectDescriptorBase { label: None };
+    assert_eq!(convert_label(&descriptor), None);
+}
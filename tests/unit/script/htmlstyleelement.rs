@@ -0,0 +1,22 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::htmlstyleelement::should_update_style_block;
+
+#[test]
+fn text_change_in_a_connected_style_element_reparses() {
+    // Setting textContent or editing a Text child of a script-created <style>
+    // in a document or in a shadow root, which is connected either way.
+    assert!(should_update_style_block(true, false));
+}
+
+#[test]
+fn parser_open_or_disconnected_style_element_waits() {
+    // The parser is still adding children: the sheet is parsed when it pops
+    // the element.
+    assert!(!should_update_style_block(true, true));
+    // A <style> in a detached subtree has no sheet to update.
+    assert!(!should_update_style_block(false, false));
+    assert!(!should_update_style_block(false, true));
+}
@@ -2,6 +2,16 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+#[cfg(test)]
+mod cssrulelist;
+#[cfg(test)]
+mod cssstylesheet;
+#[cfg(test)]
+mod dommatrix;
+#[cfg(test)]
+mod gpubuffer;
+#[cfg(test)]
+mod gpudevice;
 #[cfg(test)]
 mod headers;
 #[cfg(test)]
@@ -9,7 +19,11 @@ mod htmlareaelement;
 #[cfg(test)]
 mod htmlimageelement;
 #[cfg(test)]
+mod htmlstyleelement;
+#[cfg(test)]
 mod origin;
+#[cfg(test)]
+mod performance;
 #[cfg(all(test, target_pointer_width = "64"))]
 mod size_of;
 #[cfg(test)]
@@ -0,0 +1,438 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::{Cell, RefCell};
+use std::collections::hash_map::RandomState;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use net_traits::{ResourceAttribute, ResourceFetchTiming, ResourceTimeValue, ResourceTimingType};
+use script::test::performance::{
+    activation_start, can_add_resource_timing_entry, claim_notification_task,
+    clear_stale_secondary_entries, copy_matching_entries, entry_matches_filter, is_last_entry,
+    jittered_timing_resolution, measure_memory_if_isolated, memory_measurement,
+    previous_document_unload_timings, record_milestone, relative_time_to_unix_ms,
+    release_performance_entries, remove_matching_entries, resource_timing_entries_to_copy,
+    sort_by_start_time, supported_entry_types, timing_jitter_seed, Error, InitiatorType,
+    MemoryMeasurement, PerformanceResourceTiming, PerformanceResourceTimingMethods,
+};
+use servo_url::{ImmutableOrigin, ServoUrl};
+
+#[test]
+fn relative_time_is_offset_from_origin_epoch() {
+    // The origin was 2.5 seconds before the clocks were read at
+    // 1_000_000 ms after the epoch.
+    let origin_precise_ns = 10_000_000_000;
+    let now_precise_ns = 12_500_000_000;
+    let now_unix_ms = 1_000_000.;
+    assert_eq!(
+        relative_time_to_unix_ms(0., origin_precise_ns, now_precise_ns, now_unix_ms),
+        997_500.
+    );
+    assert_eq!(
+        relative_time_to_unix_ms(1_250.5, origin_precise_ns, now_precise_ns, now_unix_ms),
+        998_750.5
+    );
+}
+
+#[test]
+fn zero_resource_timing_limit_accepts_no_entries() {
+    assert!(!can_add_resource_timing_entry(0, 0));
+    assert_eq!(resource_timing_entries_to_copy(0, 0, 3), 0);
+}
+
+#[test]
+fn resource_timing_buffer_is_full_at_its_limit() {
+    assert!(can_add_resource_timing_entry(249, 250));
+    assert!(!can_add_resource_timing_entry(250, 250));
+    // Shrinking the limit below the current size doesn't underflow.
+    assert!(!can_add_resource_timing_entry(250, 10));
+    assert_eq!(resource_timing_entries_to_copy(250, 10, 3), 0);
+}
+
+#[test]
+fn growing_resource_timing_limit_drains_secondary_buffer() {
+    // A full buffer of 2 with 4 entries waiting, grown to 5 by a
+    // `resourcetimingbufferfull` handler, takes 3 of them.
+    assert_eq!(resource_timing_entries_to_copy(2, 5, 4), 3);
+    // Grown far enough, the whole secondary buffer is drained.
+    assert_eq!(resource_timing_entries_to_copy(2, 10, 4), 4);
+}
+
+fn clear(entries: &mut Vec<(&str, &str)>, name: Option<&str>, type_: Option<&str>) {
+    entries.retain(|&(entry_name, entry_type)| {
+        !entry_matches_filter(entry_name, entry_type, name, type_)
+    });
+}
+
+#[test]
+fn clearing_without_a_name_only_clears_that_type() {
+    let all = vec![
+        ("a", "mark"),
+        ("b", "mark"),
+        ("a", "measure"),
+        ("c", "measure"),
+    ];
+
+    // clearMarks()
+    let mut entries = all.clone();
+    clear(&mut entries, None, Some("mark"));
+    assert_eq!(entries, [("a", "measure"), ("c", "measure")]);
+
+    // clearMeasures()
+    let mut entries = all.clone();
+    clear(&mut entries, None, Some("measure"));
+    assert_eq!(entries, [("a", "mark"), ("b", "mark")]);
+}
+
+#[test]
+fn clearing_by_name_only_clears_that_name_and_type() {
+    let mut entries = vec![("a", "mark"), ("b", "mark"), ("a", "measure")];
+    // clearMarks("a")
+    clear(&mut entries, Some("a"), Some("mark"));
+    assert_eq!(entries, [("b", "mark"), ("a", "measure")]);
+}
+
+#[test]
+fn entries_are_sorted_by_start_time_then_insertion_order() {
+    // Queued out of time order, with two entries starting at the same time.
+    let mut entries = vec![("c", 30.), ("a", 10.), ("d", 30.), ("b", 20.), ("e", 10.)];
+    sort_by_start_time(&mut entries, |&(_, start_time)| start_time);
+    assert_eq!(
+        entries,
+        [("a", 10.), ("e", 10.), ("b", 20.), ("c", 30.), ("d", 30.)]
+    );
+}
+
+#[test]
+fn jittered_time_stays_within_its_bucket() {
+    for seed in 0..16 {
+        for i in 0..1000 {
+            let exact = i as f64 * 0.0037;
+            let bucket = (exact * 100.0).floor();
+            let jittered = jittered_timing_resolution(exact, seed);
+            assert!(
+                jittered == bucket / 100.0 || jittered == (bucket + 1.0) / 100.0,
+                "{} jittered to {} with seed {}",
+                exact,
+                jittered,
+                seed
+            );
+        }
+    }
+}
+
+#[test]
+fn jittered_time_never_decreases() {
+    for seed in 0..16 {
+        let mut previous = jittered_timing_resolution(0., seed);
+        for i in 1..1000 {
+            let jittered = jittered_timing_resolution(i as f64 * 0.0013, seed);
+            assert!(jittered >= previous);
+            previous = jittered;
+        }
+    }
+}
+
+#[test]
+fn jittered_time_is_deterministic_per_seed() {
+    for i in 0..100 {
+        let exact = i as f64 * 0.0071;
+        assert_eq!(
+            jittered_timing_resolution(exact, 42),
+            jittered_timing_resolution(exact, 42)
+        );
+    }
+}
+
+#[test]
+fn jitter_seed_depends_on_the_secret_key() {
+    // Otherwise any page could compute the seed of its own origin.
+    let origin = ServoUrl::parse("https://example.com/").unwrap().origin();
+    let key = RandomState::new();
+    assert_eq!(
+        timing_jitter_seed(&key, &origin),
+        timing_jitter_seed(&key, &origin)
+    );
+    assert_ne!(
+        timing_jitter_seed(&key, &origin),
+        timing_jitter_seed(&RandomState::new(), &origin)
+    );
+}
+
+#[test]
+fn opaque_origins_get_their_own_jitter_seeds() {
+    let key = RandomState::new();
+    assert_ne!(
+        timing_jitter_seed(&key, &ImmutableOrigin::new_opaque()),
+        timing_jitter_seed(&key, &ImmutableOrigin::new_opaque())
+    );
+}
+
+#[test]
+fn supported_entry_types_follow_their_prefs() {
+    let layout_shift_enabled =
+        |enabled: bool| move |entry_type: &str| entry_type != "layout-shift" || enabled;
+    assert_eq!(
+        supported_entry_types(layout_shift_enabled(true)),
+        [
+            "layout-shift",
+            "mark",
+            "measure",
+            "navigation",
+            "paint",
+            "resource"
+        ]
+    );
+    assert_eq!(
+        supported_entry_types(layout_shift_enabled(false)),
+        ["mark", "measure", "navigation", "paint", "resource"]
+    );
+}
+
+#[test]
+fn memory_is_measured_in_an_isolated_context() {
+    let measurement = measure_memory_if_isolated(true, || {
+        memory_measurement(4096, "https://example.com/".to_owned(), "Window")
+    })
+    .expect("an isolated global should be measured");
+    assert_eq!(measurement.bytes, Some(4096));
+    let breakdown = measurement.breakdown.expect("a breakdown");
+    assert_eq!(breakdown.len(), 1);
+    assert_eq!(breakdown[0].bytes, Some(4096));
+    let attribution = breakdown[0].attribution.as_ref().expect("an attribution");
+    assert_eq!(
+        attribution[0].url.as_ref().map(|url| &*url.0),
+        Some("https://example.com/")
+    );
+    assert_eq!(
+        attribution[0].scope.as_ref().map(|scope| &**scope),
+        Some("Window")
+    );
+}
+
+#[test]
+fn memory_is_not_measured_without_isolation() {
+    let measured = Cell::new(false);
+    let result = measure_memory_if_isolated(false, || {
+        measured.set(true);
+        MemoryMeasurement {
+            bytes: Some(0),
+            breakdown: None,
+        }
+    });
+    assert!(matches!(result, Err(Error::Security)));
+    assert!(!measured.get());
+}
+
+#[test]
+fn same_origin_replacement_reports_the_unload_timings() {
+    assert_eq!(
+        previous_document_unload_timings(true, 1_000, 1_250),
+        (1_000, 1_250)
+    );
+}
+
+#[test]
+fn cross_origin_replacement_hides_the_unload_timings() {
+    assert_eq!(
+        previous_document_unload_timings(false, 1_000, 1_250),
+        (0, 0)
+    );
+}
+
+#[test]
+fn clearing_after_an_overflow_drops_the_secondary_buffer() {
+    // The buffer held its limit of 2 entries and 2 more overflowed into the
+    // secondary buffer.
+    let size_limit = 2;
+    let mut secondary = VecDeque::from(vec!["stale-1", "stale-2"]);
+    clear_stale_secondary_entries(&mut secondary, false);
+    assert!(secondary.is_empty());
+    // New entries go straight into the cleared buffer, and none of the stale
+    // ones are copied after them.
+    let mut current_size = 0;
+    for _ in 0..size_limit {
+        assert!(can_add_resource_timing_entry(current_size, size_limit));
+        current_size += 1;
+    }
+    assert_eq!(
+        resource_timing_entries_to_copy(current_size, size_limit, secondary.len()),
+        0
+    );
+}
+
+#[test]
+fn clearing_from_a_buffer_full_handler_keeps_the_secondary_buffer() {
+    let mut secondary = VecDeque::from(vec!["waiting"]);
+    clear_stale_secondary_entries(&mut secondary, true);
+    assert_eq!(secondary, ["waiting"]);
+    // The cleared buffer now has room for the waiting entry.
+    assert_eq!(resource_timing_entries_to_copy(0, 2, secondary.len()), 1);
+}
+
+#[test]
+fn buffered_observers_registered_in_one_tick_share_a_notification_task() {
+    let pending = Cell::new(false);
+    // Five buffered observers register before the task runs: only the first
+    // queues it.
+    let queued = (0..5).filter(|_| claim_notification_task(&pending)).count();
+    assert_eq!(queued, 1);
+    assert!(pending.get());
+}
+
+#[test]
+fn a_new_notification_task_is_queued_once_the_last_one_ran() {
+    let pending = Cell::new(false);
+    assert!(claim_notification_task(&pending));
+    // notify_observers() clears the flag when the task runs.
+    pending.set(false);
+    assert!(claim_notification_task(&pending));
+    assert!(!claim_notification_task(&pending));
+}
+
+#[test]
+fn queuing_the_same_entry_twice_buffers_it_once() {
+    let mut buffer: Vec<Rc<&str>> = vec![];
+    let entry = Rc::new("https://example.com/image.png");
+    for _ in 0..2 {
+        if !is_last_entry(&buffer, &*entry) {
+            buffer.push(entry.clone());
+        }
+    }
+    assert_eq!(buffer.len(), 1);
+}
+
+#[test]
+fn equal_entries_from_separate_fetches_are_both_buffered() {
+    let first = Rc::new("https://example.com/image.png");
+    let second = Rc::new("https://example.com/image.png");
+    let buffer = vec![first];
+    assert!(!is_last_entry(&buffer, &*second));
+    assert!(!is_last_entry::<&str, Rc<&str>>(&[], &*second));
+}
+
+#[test]
+fn normal_navigation_has_no_activation_start() {
+    assert_eq!(activation_start(None, 1_000), 0.);
+}
+
+#[test]
+fn activation_start_is_relative_to_the_navigation_start() {
+    assert_eq!(activation_start(Some(1_250), 1_000), 250.);
+}
+
+#[test]
+fn disabling_the_buffer_releases_every_entry() {
+    let entry = Rc::new("https://example.com/script.js");
+    let mut buffer = vec![entry.clone()];
+    let mut secondary = VecDeque::from(vec![entry.clone()]);
+    // An observer with its own list of entries to deliver.
+    let observer_entries = Rc::new(RefCell::new(vec![entry.clone()]));
+    let mut observers = vec![observer_entries.clone()];
+    assert_eq!(Rc::strong_count(&entry), 4);
+
+    release_performance_entries(&mut buffer, &mut secondary, &mut observers, |observer| {
+        observer.borrow_mut().clear()
+    });
+    assert!(buffer.is_empty());
+    assert!(secondary.is_empty());
+    assert!(observers.is_empty());
+    assert!(observer_entries.borrow().is_empty());
+    // Nothing but the test holds the entry now, so it can be collected.
+    assert_eq!(Rc::strong_count(&entry), 1);
+}
+
+#[test]
+fn load_milestones_are_nonzero_and_ordered() {
+    let dom_interactive = Cell::new(0);
+    let dom_content_loaded_event_start = Cell::new(0);
+    let dom_content_loaded_event_end = Cell::new(0);
+    let dom_complete = Cell::new(0);
+    // A full load reaches each milestone in turn.
+    record_milestone(&dom_interactive, 1_000);
+    record_milestone(&dom_content_loaded_event_start, 1_010);
+    record_milestone(&dom_content_loaded_event_end, 1_015);
+    record_milestone(&dom_complete, 1_200);
+    // Reaching one again later, e.g. by setting the ready state twice, must
+    // not move it.
+    record_milestone(&dom_interactive, 1_300);
+
+    let milestones = [
+        dom_interactive.get(),
+        dom_content_loaded_event_start.get(),
+        dom_content_loaded_event_end.get(),
+        dom_complete.get(),
+    ];
+    assert!(milestones.iter().all(|&milestone| milestone > 0));
+    assert!(milestones.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert_eq!(dom_interactive.get(), 1_000);
+}
+
+#[test]
+fn clearing_marks_leaves_a_buffered_observers_copies_alone() {
+    let is_mark =
+        |entry: &Rc<(&str, &str)>| entry_matches_filter(entry.0, entry.1, None, Some("mark"));
+    let mut buffer = vec![
+        Rc::new(("a", "mark")),
+        Rc::new(("m", "measure")),
+        Rc::new(("b", "mark")),
+    ];
+    // A `buffered` mark observer is replayed the marks already buffered.
+    let delivered = copy_matching_entries(&buffer, is_mark);
+
+    // clearMarks()
+    remove_matching_entries(&mut buffer, is_mark);
+    assert_eq!(buffer, [Rc::new(("m", "measure"))]);
+
+    // The observer still has both marks it was handed.
+    assert_eq!(delivered, [Rc::new(("a", "mark")), Rc::new(("b", "mark"))]);
+}
+
+#[test]
+fn resource_entry_without_timing_allow_origin_hides_restricted_attributes() {
+    let mut resource_timing = ResourceFetchTiming::new(ResourceTimingType::Resource);
+    resource_timing.set_attribute(ResourceAttribute::FetchStart);
+    resource_timing.set_attribute(ResourceAttribute::StartTime(ResourceTimeValue::FetchStart));
+    resource_timing.set_attribute(ResourceAttribute::DomainLookupStart);
+    resource_timing.set_attribute(ResourceAttribute::ConnectStart(1));
+    resource_timing.set_attribute(ResourceAttribute::SecureConnectionStart);
+    resource_timing.set_attribute(ResourceAttribute::ConnectEnd(2));
+    resource_timing.set_attribute(ResourceAttribute::RequestStart);
+    // The check fails once the response arrived, and the body comes after.
+    resource_timing.mark_timing_check_failed();
+    assert!(!resource_timing.timing_check_passed);
+    resource_timing.set_attribute(ResourceAttribute::ResponseStart);
+    resource_timing.set_body_sizes(10, 20);
+    resource_timing.set_attribute(ResourceAttribute::ResponseEnd);
+
+    let entry = PerformanceResourceTiming::from_resource_timing(
+        ServoUrl::parse("https://cross-origin.example/image.png").unwrap(),
+        InitiatorType::Other,
+        None,
+        &resource_timing,
+    );
+    let restricted = [
+        entry.WorkerStart(),
+        entry.RedirectStart(),
+        entry.RedirectEnd(),
+        entry.DomainLookupStart(),
+        entry.DomainLookupEnd(),
+        entry.ConnectStart(),
+        entry.ConnectEnd(),
+        entry.SecureConnectionStart(),
+        entry.RequestStart(),
+        entry.ResponseStart(),
+    ];
+    assert!(
+        restricted.iter().all(|time| **time == 0.),
+        "restricted timestamps should be zero"
+    );
+    assert_eq!(entry.TransferSize(), 0);
+    assert_eq!(entry.EncodedBodySize(), 0);
+    assert_eq!(entry.DecodedBodySize(), 0);
+    assert_eq!(String::from(entry.NextHopProtocol()), "");
+    assert!(*entry.FetchStart() > 0., "fetchStart should remain");
+    assert!(*entry.ResponseEnd() > 0., "responseEnd should remain");
+}
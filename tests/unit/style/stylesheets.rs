@@ -10,8 +10,10 @@ use servo_url::ServoUrl;
 use style::context::QuirksMode;
 use style::error_reporting::{ContextualParseError, ParseErrorReporter};
 use style::media_queries::MediaList;
-use style::shared_lock::SharedRwLock;
-use style::stylesheets::{AllowImportRules, Origin, Stylesheet};
+use style::shared_lock::{Locked, SharedRwLock, ToCssWithGuard};
+use style::stylesheets::{
+    AllowImportRules, CssRule, CssRules, CssRulesHelpers, Origin, RulesMutateError, Stylesheet,
+};
 
 #[derive(Debug)]
 struct CSSError {
@@ -229,3 +231,206 @@ fn test_source_url() {
         assert_eq!(*url_opt, test.1);
     }
 }
+
+fn rule_selectors(stylesheet: &Stylesheet, rules: &Locked<CssRules>) -> Vec<String> {
+    let guard = stylesheet.shared_lock.read();
+    rules
+        .read_with(&guard)
+        .0
+        .iter()
+        .map(|rule| {
+            let css = rule.to_css_string(&guard);
+            css.split(' ').next().unwrap().to_owned()
+        })
+        .collect()
+}
+
+#[test]
+fn test_insert_rule_index_in_media_rule() {
+    let url = ServoUrl::parse("about::test").unwrap();
+    let lock = SharedRwLock::new();
+    let media = Arc::new(lock.wrap(MediaList::empty()));
+    let stylesheet = Stylesheet::from_str(
+        "a {} @media screen { b {} c {} }",
+        url,
+        Origin::Author,
+        media,
+        lock,
+        None,
+        None,
+        QuirksMode::NoQuirks,
+        0,
+        AllowImportRules::Yes,
+    );
+    let media_rules = {
+        let guard = stylesheet.shared_lock.read();
+        match stylesheet.contents.rules.read_with(&guard).0[1] {
+            CssRule::Media(ref rule) => rule.rules.clone(),
+            _ => panic!("expected a @media rule"),
+        }
+    };
+
+    // Each inserted rule must land at the requested index of the nested
+    // list, which is the index `insertRule` reports back.
+    for (index, selector) in [(0, "x"), (2, "y"), (4, "z")] {
+        let inserted = media_rules.insert_rule(
+            &stylesheet.shared_lock,
+            &format!("{} {{}}", selector),
+            &stylesheet.contents,
+            index,
+            /* nested */ true,
+            None,
+            AllowImportRules::Yes,
+        );
+        assert!(inserted.is_ok());
+        assert_eq!(rule_selectors(&stylesheet, &media_rules)[index], selector);
+    }
+    assert_eq!(
+        rule_selectors(&stylesheet, &media_rules),
+        ["x", "b", "y", "c", "z"]
+    );
+    assert_eq!(
+        rule_selectors(&stylesheet, &stylesheet.contents.rules),
+        ["a", "@media"]
+    );
+
+    let result = media_rules.insert_rule(
+        &stylesheet.shared_lock,
+        "w {}",
+        &stylesheet.contents,
+        6,
+        /* nested */ true,
+        None,
+        AllowImportRules::Yes,
+    );
+    assert!(matches!(result, Err(RulesMutateError::IndexSize)));
+}
+
+#[test]
+fn test_remove_rule_index() {
+    let url = ServoUrl::parse("about::test").unwrap();
+    let lock = SharedRwLock::new();
+    let media = Arc::new(lock.wrap(MediaList::empty()));
+    let stylesheet = Stylesheet::from_str(
+        "a {} b {} c {}",
+        url,
+        Origin::Author,
+        media,
+        lock,
+        None,
+        None,
+        QuirksMode::NoQuirks,
+        0,
+        AllowImportRules::Yes,
+    );
+    let rules = &stylesheet.contents.rules;
+    let remove_rule = |index| {
+        let mut guard = stylesheet.shared_lock.write();
+        rules.write_with(&mut guard).remove_rule(index)
+    };
+
+    assert!(remove_rule(0).is_ok());
+    assert_eq!(rule_selectors(&stylesheet, rules), ["b", "c"]);
+    // The length itself is out of range, but the last index isn't.
+    assert!(matches!(remove_rule(2), Err(RulesMutateError::IndexSize)));
+    assert!(remove_rule(1).is_ok());
+    assert_eq!(rule_selectors(&stylesheet, rules), ["b"]);
+    assert!(matches!(remove_rule(5), Err(RulesMutateError::IndexSize)));
+    assert_eq!(rule_selectors(&stylesheet, rules), ["b"]);
+}
+
+#[test]
+fn test_insert_rule_placement_constraints() {
+    let url = ServoUrl::parse("about::test").unwrap();
+    let lock = SharedRwLock::new();
+    let media = Arc::new(lock.wrap(MediaList::empty()));
+    let stylesheet = Stylesheet::from_str(
+        "a {}",
+        url,
+        Origin::Author,
+        media,
+        lock,
+        None,
+        None,
+        QuirksMode::NoQuirks,
+        0,
+        AllowImportRules::Yes,
+    );
+    let rules = &stylesheet.contents.rules;
+    let insert_rule = |rule: &str, index| {
+        rules.insert_rule(
+            &stylesheet.shared_lock,
+            rule,
+            &stylesheet.contents,
+            index,
+            /* nested */ false,
+            None,
+            AllowImportRules::Yes,
+        )
+    };
+
+    // @import may not follow a style rule.
+    assert!(matches!(
+        insert_rule("@import url(b.css);", 1),
+        Err(RulesMutateError::HierarchyRequest)
+    ));
+    // @namespace may only be inserted into a list of @import and @namespace
+    // rules.
+    assert!(matches!(
+        insert_rule("@namespace svg url(http://www.w3.org/2000/svg);", 0),
+        Err(RulesMutateError::InvalidState)
+    ));
+    // A bare declaration isn't a rule at all.
+    assert!(matches!(
+        insert_rule("color: red", 0),
+        Err(RulesMutateError::Syntax)
+    ));
+    assert_eq!(rule_selectors(&stylesheet, rules), ["a"]);
+
+    // Group rules are fine anywhere after the prelude rules.
+    assert!(insert_rule("@media screen { b {} }", 1).is_ok());
+    assert_eq!(rule_selectors(&stylesheet, rules), ["a", "@media"]);
+}
+
+#[test]
+fn test_insert_rule_uses_the_quirks_mode_of_the_sheet() {
+    let inserted_rule = |quirks_mode| {
+        let url = ServoUrl::parse("about::test").unwrap();
+        let lock = SharedRwLock::new();
+        let media = Arc::new(lock.wrap(MediaList::empty()));
+        let stylesheet = Stylesheet::from_str(
+            "",
+            url,
+            Origin::Author,
+            media,
+            lock,
+            None,
+            None,
+            quirks_mode,
+            0,
+            AllowImportRules::Yes,
+        );
+        let rules = &stylesheet.contents.rules;
+        rules
+            .insert_rule(
+                &stylesheet.shared_lock,
+                "b { background-color: ff0000 }",
+                &stylesheet.contents,
+                0,
+                /* nested */ false,
+                None,
+                AllowImportRules::Yes,
+            )
+            .unwrap();
+        let guard = stylesheet.shared_lock.read();
+        let rules = rules.read_with(&guard);
+        rules.0[0].to_css_string(&guard)
+    };
+
+    // The hashless color quirk only applies in quirks mode.
+    assert_eq!(
+        inserted_rule(QuirksMode::Quirks),
+        "b { background-color: rgb(255, 0, 0); }"
+    );
+    assert_eq!(inserted_rule(QuirksMode::NoQuirks), "b { }");
+}
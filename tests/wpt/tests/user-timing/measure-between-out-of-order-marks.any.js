@@ -0,0 +1,23 @@
+// META: global=window,worker
+// META: title=A measure from a later mark to an earlier one has a negative duration
+
+test(() => {
+  performance.mark("earlier");
+  const earlierTime = performance.now();
+  while (performance.now() <= earlierTime) {}
+  performance.mark("later");
+
+  performance.measure("backwards", "later", "earlier");
+  const earlier = performance.getEntriesByName("earlier", "mark")[0];
+  const later = performance.getEntriesByName("later", "mark")[0];
+  const measures = performance.getEntriesByName("backwards", "measure");
+  assert_equals(measures.length, 1, "the measure is buffered");
+  assert_equals(measures[0].startTime, later.startTime);
+  assert_equals(measures[0].duration, earlier.startTime - later.startTime);
+  assert_less_than(measures[0].duration, 0, "the duration is not clamped to 0");
+
+  performance.clearMeasures("backwards");
+  assert_array_equals(performance.getEntriesByName("backwards"), []);
+  assert_equals(performance.getEntriesByType("mark").length, 2,
+                "clearMeasures() leaves the marks alone");
+}, "measure() between out-of-order marks, then clearMeasures()");